@@ -0,0 +1,24 @@
+// Curved screen with scanlines and a vignette.
+const float CURVATURE = 0.08;
+const float SCANLINES = 0.3;
+const float VIGNETTE = 0.35;
+
+vec2 curve(vec2 uv) {
+    uv = uv * 2.0 - 1.0;
+    uv *= 1.0 + CURVATURE * dot(uv.yx, uv.yx);
+    return uv * 0.5 + 0.5;
+}
+
+void main() {
+    vec2 uv = curve(v_tex);
+    if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        frag_color = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+    vec3 color = texture(u_frame, uv).rgb;
+    float scanline = 0.5 + 0.5 * cos(uv.y * u_source_size.y * 6.2831853);
+    color *= 1.0 - SCANLINES * scanline;
+    vec2 center = uv - 0.5;
+    color *= 1.0 - VIGNETTE * dot(center, center) * 2.0;
+    frag_color = vec4(color, 1.0);
+}
@@ -0,0 +1,18 @@
+// Separates the Lynx pixels with a thin dark grid and splits them in RGB stripes.
+const float GRID = 0.12;
+const float STRIPES = 0.25;
+
+void main() {
+    vec2 pixel = v_tex * u_source_size;
+    vec2 cell = fract(pixel);
+    vec4 color = texture(u_frame, (floor(pixel) + 0.5) / u_source_size);
+
+    float edge = step(1.0 - GRID, max(cell.x, cell.y));
+    vec3 mask = vec3(1.0 - STRIPES);
+    int stripe = int(cell.x * 3.0);
+    if (stripe == 0) mask.r = 1.0;
+    else if (stripe == 1) mask.g = 1.0;
+    else mask.b = 1.0;
+
+    frag_color = vec4(color.rgb * mask * (1.0 - 0.6 * edge), 1.0);
+}
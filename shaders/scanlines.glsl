@@ -0,0 +1,8 @@
+// Darkens every other output line, the Lynx pixel rows stay sharp.
+const float STRENGTH = 0.35;
+
+void main() {
+    vec4 color = texture(u_frame, v_tex);
+    float line = mod(floor(v_tex.y * u_output_size.y), 2.0);
+    frag_color = vec4(color.rgb * (1.0 - STRENGTH * line), 1.0);
+}
@@ -1,10 +1,10 @@
-use std::path::PathBuf;
+use std::{cell::RefCell, path::{Path, PathBuf}, rc::Rc, time::Duration};
 
-use gtk::{Application, ApplicationWindow, gdk, gio::{self}, glib::{self, clone}, prelude::{ActionExt, ActionMapExtManual, ButtonExt, FileExt, GridExt, GtkApplicationExt, GtkWindowExt, ObjectExt, WidgetExt}};
-use holani::suzy::registers::{Joystick, Switches};
+use gtk::{ApplicationWindow, gdk, gio::{self}, glib::{self, clone}, prelude::{ActionExt, ActionMapExt, ActionMapExtManual, ButtonExt, Cast, CheckButtonExt, EditableExt, FileExt, GridExt, GtkApplicationExt, GtkWindowExt, ListBoxRowExt, ObjectExt, RangeExt, ScaleExt, StaticVariantType, ToVariant, WidgetExt}};
+use holani::{mikey::video::{LYNX_SCREEN_HEIGHT, LYNX_SCREEN_WIDTH}, suzy::registers::{Joystick, Switches}};
 use log::error;
 use strum::IntoEnumIterator;
-use crate::{lynx_display::LynxDisplay, runner::{runner_config::{Input, RunnerAction, RunnerConfig, RunnerStatus}, Runner}, Event};
+use crate::{cart_watcher::CartWatcher, cartridge::{archive::{archive_entries, read_cart_file, ARCHIVE_EXTENSIONS}, is_headerless, lnx_header::{LnxHeader, BANK_PAGE_SIZES, EEPROM_TYPES, LNX_HEADER_SIZE, ROTATIONS}, patch::PATCH_EXTENSIONS, CartInfo, CART_EXTENSIONS, HEADERLESS_EXTENSIONS}, cheat_manager::CheatManager, cheats::{load_cheats, save_cheats, Cheat}, debugger::{audio_inspector::AudioInspector, profiler_view::ProfilerView, ram_search::RamSearch, suzy_inspector::SuzyInspector, video_viewer::VideoViewer, DebugSnapshot, DebugView}, library::{scan, thumbnail_file, Library, PlayHistory}, lynx_display::{gl_display_widget, shader_path, shaders, LynxDisplay}, runner::{frame_exchange::FrameReader, profiler::ProfileReport, runner_config::{cart_file_name, data_file, ColorAdjustments, ColorProfile, Input, OsdElement, Renderer, RunnerAction, RunnerConfig, RunnerStatus, Scaler, SerialBacking, TraceSettings, WatchRestore, MAX_COMLYNX_UNITS}, stats::RunnerStats, Runner}, Event};
 
 macro_rules! btn_event {
     ($win: ident, $event_tx: expr, $cmd: expr, $mne: expr, $evt: expr) => {
@@ -23,20 +23,41 @@ macro_rules! btn_event {
 }
 
 
+const MAX_WINDOW_SCALE: u8 = 8;
+/// Pointer inactivity after which the cursor is hidden in fullscreen.
+const CURSOR_HIDE_DELAY: Duration = Duration::from_secs(2);
+/// Quiet time after the last change of the watched cart file before reloading it, the linker
+/// may write it in several steps.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
 pub struct App {
+    application: gtk::Application,
+    window: gtk::ApplicationWindow,
     display: LynxDisplay,
+    guest_displays: Vec<LynxDisplay>,
+    tiles: gtk::Grid,
     runner: Runner,
     config: RunnerConfig,
-    input_tx: kanal::Sender<(u8, u8)>,
+    input_tx: kanal::Sender<(usize, u8, u8)>,
     config_tx: kanal::Sender<RunnerConfig>,
+    frame_readers: Vec<FrameReader>,
     event_tx: kanal::Sender<Event>,
-    joy: Joystick,
-    switches: Switches,
+    inputs: Vec<(Joystick, Switches)>,
+    debug_views: Vec<Box<dyn DebugView>>,
+    profiler_view: Option<ProfilerView>,
+    cheat_manager: Option<CheatManager>,
+    cart_hash: Option<u32>,
+    library: Option<Library>,
+    play_history: PlayHistory,
+    recent_menu: gio::Menu,
+    cart_watcher: Option<CartWatcher>,
+    watch_reload: Rc<RefCell<Option<glib::SourceId>>>,
+    /// Set when the GL renderer couldn't start, the software one is used instead.
+    gl_failed: bool,
 }
 
 impl App {
     pub fn new(app: &gtk::Application, event_tx: kanal::Sender<Event>, conf: RunnerConfig) -> Self {
-        
         let mut config = match confy::load::<RunnerConfig>("holani-gtk", None) {
             Err(e) => {
                 error!("Couldn't load settings. Using defaults. '{}'", e);
@@ -53,31 +74,64 @@ impl App {
         }
 
         config.set_single_instance(conf.single_instance());
+        config.set_fullscreen(conf.fullscreen());
 
         let mut runner = Runner::new();
 
-        let (input_tx, config_tx, rotation) = runner.initialize_thread(event_tx.clone(), config.clone());
+        let (input_tx, config_tx, frame_readers, rotation) = runner.initialize_thread(event_tx.clone(), config.clone());
 
         config.set_rotation(rotation);
 
+        let window = ApplicationWindow::builder()
+            .application(app)
+            .title("Holani")
+            .show_menubar(true)
+            .build();
+
         let mut slf = Self {
+            application: app.clone(),
+            window,
             display: LynxDisplay::default(),
+            guest_displays: vec![],
+            tiles: gtk::Grid::builder()
+                .row_homogeneous(true)
+                .column_homogeneous(true)
+                .build(),
             runner,
             config,          
             input_tx,
             config_tx,
+            frame_readers,
             event_tx,
-            joy: Joystick::empty(),
-            switches: Switches::empty(),
+            inputs: vec![(Joystick::empty(), Switches::empty()); MAX_COMLYNX_UNITS as usize],
+            debug_views: vec![],
+            profiler_view: None,
+            cheat_manager: None,
+            cart_hash: None,
+            library: None,
+            play_history: PlayHistory::load(),
+            recent_menu: gio::Menu::new(),
+            cart_watcher: None,
+            watch_reload: Rc::new(RefCell::new(None)),
+            gl_failed: false,
         };
 
-        slf.build_ui(app);
+        slf.build_ui();
+        slf.update_cart_watcher();
 
         slf
     }
 
-    pub fn setup_next_frame(&mut self, data: &Vec<u8>) {
-        self.display.setup_next_frame(data);
+    pub fn setup_next_frame(&mut self, unit: usize) {
+        let Some(data) = self.frame_readers.get_mut(unit).and_then(|reader| reader.take()) else {
+            return;
+        };
+        match unit {
+            0 => self.display.setup_next_frame(data),
+            _ => if let Some(display) = self.guest_displays.get(unit - 1) {
+                display.setup_next_frame(data);
+            }
+        }
     }
 
     pub fn set_new_config(&mut self, config: RunnerConfig) {
@@ -87,22 +141,25 @@ impl App {
 
     fn key_changed(&mut self, key: gdk::Key, value: bool) {
         let kstr = key.name().unwrap().to_lowercase();
-        if !self.config.button_mapping().contains_key(&kstr) {
-            return;
+
+        for unit in 0..self.config.units() {
+            let Some(input) = self.config.unit_button_mapping(unit).and_then(|m| m.get(&kstr)) else {
+                continue;
+            };
+            let (joy, switches) = &mut self.inputs[unit];
+            match input {
+                Input::Up => joy.set(Joystick::up, value),
+                Input::Down => joy.set(Joystick::down, value),
+                Input::Left => joy.set(Joystick::left, value),
+                Input::Right => joy.set(Joystick::right, value),
+                Input::Outside => joy.set(Joystick::outside, value),
+                Input::Inside => joy.set(Joystick::inside, value),
+                Input::Option1 => joy.set(Joystick::option_1, value),
+                Input::Option2 => joy.set(Joystick::option_2, value),
+                Input::Pause => switches.set(Switches::pause, value),
+            };
+            self.input_tx.send((unit, joy.bits(), switches.bits())).unwrap();
         }
-        
-        match self.config.button_mapping()[&kstr] {
-            Input::Up => self.joy.set(Joystick::up, value),
-            Input::Down => self.joy.set(Joystick::down, value),
-            Input::Left => self.joy.set(Joystick::left, value),
-            Input::Right => self.joy.set(Joystick::right, value),
-            Input::Outside => self.joy.set(Joystick::outside, value),
-            Input::Inside => self.joy.set(Joystick::inside, value),
-            Input::Option1 => self.joy.set(Joystick::option_1, value),
-            Input::Option2 => self.joy.set(Joystick::option_2, value),
-            Input::Pause => self.switches.set(Switches::pause, value),
-        };
-        self.input_tx.send((self.joy.bits(), self.switches.bits())).unwrap();
     }
 
     pub fn key_pressed(&mut self, key: gdk::Key) {
@@ -113,24 +170,85 @@ impl App {
         self.key_changed(key, false);
     }
 
-    fn build_ui(&mut self, app: &Application) {
+    fn displays(&self) -> impl Iterator<Item = &LynxDisplay> {
+        std::iter::once(&self.display).chain(self.guest_displays.iter())
+    }
 
-        let picture = gtk::Picture::builder()
-            .paintable(&self.display)
-            .hexpand(true)
-            .vexpand(true)
-            .halign(gtk::Align::Fill)
-            .valign(gtk::Align::Fill)
-            .content_fit(gtk::ContentFit::Contain)
-            .can_shrink(true)
-            .build();
+    /// Applies the video settings to a display.
+    fn configure_display(&self, display: &LynxDisplay) {
+        display.set_ghosting(self.config.ghosting());
+        display.set_scaler(self.config.scaler());
+        display.set_lcd_grid(self.config.lcd_grid());
+        display.set_color_correction(self.config.color_profile().adjustments(self.config.color_adjustments()));
+        for element in OsdElement::iter() {
+            display.set_osd(element, self.config.osd(element));
+        }
+    }
 
-        let window = ApplicationWindow::builder()
-            .application(app)
-            .title("Holani")
-            .child(&picture)
-            .show_menubar(true)
-            .build();    
+    fn build_ui(&mut self) {
+        self.configure_display(&self.display);
+        self.layout_tiles();
+
+        let window = self.window.clone();
+        window.set_child(Some(&self.tiles));
+        // GTK4 doesn't let applications place their windows, only the size is restored.
+        if let Some((width, height)) = self.config.window_size() {
+            window.set_default_size(width, height);
+        }
+        if self.config.window_maximized() {
+            window.maximize();
+        }
+
+        let hide_cursor: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
+
+        window.connect_fullscreened_notify(clone!(
+            #[strong] hide_cursor,
+            move |window| {
+                let fullscreen = window.is_fullscreen();
+                window.set_show_menubar(!fullscreen);
+                if let Some(source) = hide_cursor.take() {
+                    source.remove();
+                }
+                window.set_cursor_from_name(if fullscreen { Some("none") } else { None });
+                if let Some(action) = window.application().and_then(|app| app.lookup_action("fullscreen")) {
+                    action.change_state(&fullscreen.into());
+                }
+            }));
+
+        let motion_controller = gtk::EventControllerMotion::new();
+        motion_controller.connect_motion(clone!(
+            #[weak] window,
+            #[strong] hide_cursor,
+            move |_, _, _| {
+                if !window.is_fullscreen() {
+                    return;
+                }
+                window.set_cursor(None::<&gdk::Cursor>);
+                if let Some(source) = hide_cursor.take() {
+                    source.remove();
+                }
+                hide_cursor.replace(Some(glib::timeout_add_local_once(CURSOR_HIDE_DELAY, clone!(
+                    #[weak] window,
+                    #[strong] hide_cursor,
+                    move || {
+                        hide_cursor.take();
+                        window.set_cursor_from_name(Some("none"));
+                    }
+                ))));
+            }));
+        window.add_controller(motion_controller);
+
+        if self.config.fullscreen() {
+            window.fullscreen();
+        }
+
+        let tx = self.event_tx.clone();
+        window.connect_close_request(clone!(
+            #[strong] tx,
+            move |_| {
+                tx.send(Event::Quit).unwrap();
+                glib::Propagation::Stop
+            }));
 
         let event_controller = gtk::EventControllerKey::new();
 
@@ -155,12 +273,56 @@ impl App {
         window.present();        
     }
 
+    /// Lays the displays of the ComLynx units out in a square-ish grid.
+    fn layout_tiles(&mut self) {
+        let units = self.config.units();
+        self.guest_displays.truncate(units - 1);
+        while self.guest_displays.len() < units - 1 {
+            let display = LynxDisplay::default();
+            self.configure_display(&display);
+            self.guest_displays.push(display);
+        }
+
+        while let Some(child) = self.tiles.first_child() {
+            self.tiles.remove(&child);
+        }
+
+        let columns = (units as f64).sqrt().ceil() as usize;
+        for (i, display) in self.displays().enumerate() {
+            self.tiles.attach(&self.display_widget(display), (i % columns) as i32, (i / columns) as i32, 1, 1);
+        }
+    }
+
+    fn display_widget(&self, display: &LynxDisplay) -> gtk::Widget {
+        if self.config.renderer() == Renderer::Gl && !self.gl_failed {
+            let tx = self.event_tx.clone();
+            return gl_display_widget(
+                display,
+                display_picture(display),
+                self.config.shader().and_then(shader_path),
+                move |e| tx.send(Event::RendererFailed(e)).unwrap(),
+            );
+        }
+        display.set_overlay_only(false);
+        display_picture(display).upcast()
+    }
+
     fn build_menu(&self,  window: &gtk::ApplicationWindow) {
-    
         btn_event!(window, self.event_tx, "about", "<Alt>a", Event::About);
         btn_event!(window, self.event_tx, "exit", "<Alt>x", Event::Quit);
         btn_event!(window, self.event_tx, "reload_cart", "<Alt>r", Event::ReloadCart);
         btn_event!(window, self.event_tx, "reset", "<Alt>t", Event::Reset);
+        btn_event!(window, self.event_tx, "trace_settings", "<Alt>g", Event::TraceSettings);
+        btn_event!(window, self.event_tx, "suzy_inspector", "<Alt>i", Event::SuzyInspector);
+        btn_event!(window, self.event_tx, "video_viewer", "<Alt>v", Event::VideoViewer);
+        btn_event!(window, self.event_tx, "audio_inspector", "<Alt>u", Event::AudioInspector);
+        btn_event!(window, self.event_tx, "profiler", "<Alt>f", Event::Profiler);
+        btn_event!(window, self.event_tx, "cheats", "<Alt>h", Event::CheatManager);
+        btn_event!(window, self.event_tx, "ram_search", "<Alt>n", Event::RamSearch);
+        btn_event!(window, self.event_tx, "buttons", "<Alt>b", Event::ButtonsMapping(0));
+        btn_event!(window, self.event_tx, "color_settings", "<Alt>k", Event::ColorSettings);
+        btn_event!(window, self.event_tx, "library", "<Alt>y", Event::Library);
+        btn_event!(window, self.event_tx, "cart_info", "<Alt>j", Event::CartridgeInfo);
         
         let tx = self.event_tx.clone();
         let app = window.application().unwrap();
@@ -169,6 +331,9 @@ impl App {
             let file_menu = {            
                 let load_cart_menu_item = gio::MenuItem::new(Some("Load _cart"), Some("app.load_cart"));
                 let reload_cart_menu_item = gio::MenuItem::new(Some("_Reload cart"), Some("app.reload_cart"));
+                let library_menu_item = gio::MenuItem::new(Some("Librar_y"), Some("app.library"));
+                let load_patch_menu_item = gio::MenuItem::new(Some("Apply _patch"), Some("app.load_patch"));
+                let auto_patch_menu_item = gio::MenuItem::new(Some("A_uto-apply patches"), Some("app.auto_patch"));
                 let load_state_menu_item = gio::MenuItem::new(Some("_Load state"), Some("app.load_state"));
                 let save_state_item = gio::MenuItem::new(Some("_Save state"), Some("app.save_state"));
                 let quit_menu_item = gio::MenuItem::new(Some("E_xit"), Some("app.exit"));
@@ -181,6 +346,26 @@ impl App {
                     ))
                     .build();
 
+                let load_patch_action = gio::ActionEntry::builder("load_patch")
+                    .activate(clone!(
+                        #[strong] tx,
+                        #[weak] window,
+                        move |_, _, _| show_patch_picker(tx.clone(), &window)
+                    ))
+                    .build();
+
+                let auto_patch_action = gio::ActionEntry::builder("auto_patch")
+                    .state(self.config.auto_patch().into())
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, action, _| {
+                            let checked = !action.state().unwrap().get::<bool>().unwrap();
+                            action.set_state(&checked.into());
+                            tx.send(Event::AutoPatch(checked)).unwrap();
+                        })
+                    )
+                    .build();
+
                 let load_state_action = gio::ActionEntry::builder("load_state")
                     .activate(clone!(
                         #[strong] tx,
@@ -197,7 +382,37 @@ impl App {
                     ))
                     .build();
 
-                app.add_action_entries([load_cart_action, load_state_action, save_state_action]);
+                let recent_action = gio::ActionEntry::builder("recent")
+                    .parameter_type(Some(&String::static_variant_type()))
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, _, param| {
+                            let file = param.unwrap().get::<String>().unwrap();
+                            tx.send(Event::LoadCart(PathBuf::from(file))).unwrap();
+                        })
+                    )
+                    .build();
+
+                let clear_recent_action = gio::ActionEntry::builder("clear_recent")
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, _, _| tx.send(Event::ClearRecentCarts).unwrap()
+                    ))
+                    .build();
+
+                let auto_resume_action = gio::ActionEntry::builder("auto_resume")
+                    .state(self.config.auto_resume().into())
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, action, _| {
+                            let checked = !action.state().unwrap().get::<bool>().unwrap();
+                            action.set_state(&checked.into());
+                            tx.send(Event::AutoResume(checked)).unwrap();
+                        })
+                    )
+                    .build();
+
+                app.add_action_entries([load_cart_action, load_patch_action, auto_patch_action, load_state_action, save_state_action, recent_action, clear_recent_action, auto_resume_action]);
                 app.set_accels_for_action("app.load_cart", &["<Alt>c"]); 
                 app.set_accels_for_action("app.load_state", &["<Alt>l"]); 
                 app.set_accels_for_action("app.save_state", &["<Alt>s"]); 
@@ -205,9 +420,17 @@ impl App {
                 let file_menu = gio::Menu::new();
                 file_menu.append_item(&load_cart_menu_item);
                 file_menu.append_item(&reload_cart_menu_item);
+                file_menu.append_submenu(Some("Rece_nt"), &self.recent_menu);
+                file_menu.append_item(&library_menu_item);
+                self.refresh_recent_menu();
+                let patch_menu = gio::Menu::new();
+                patch_menu.append_item(&load_patch_menu_item);
+                patch_menu.append_item(&auto_patch_menu_item);
+                file_menu.append_section(None, &patch_menu);
                 let state_menu = gio::Menu::new();
                 state_menu.append_item(&load_state_menu_item);
                 state_menu.append_item(&save_state_item);
+                state_menu.append_item(&gio::MenuItem::new(Some("_Auto-resume"), Some("app.auto_resume")));
                 file_menu.append_section(None, &state_menu);
                 let exit_menu = gio::Menu::new();
                 exit_menu.append_item(&quit_menu_item);
@@ -219,6 +442,7 @@ impl App {
                 let pause_menu_item = gio::MenuItem::new(Some("_Pause"), Some("app.pause"));
                 let mute_state_item = gio::MenuItem::new(Some("_Mute"), Some("app.mute"));
                 let keys_menu_item = gio::MenuItem::new(Some("_Buttons mapping"), Some("app.buttons"));
+                let cheats_menu_item = gio::MenuItem::new(Some("C_heats"), Some("app.cheats"));
                 let rom_header = match self.config.rom() {
                     None => "R_OM (Free Boot)".to_string(),
                     Some(path) => format!("R_OM ({:?})", path.file_name().unwrap()),
@@ -257,25 +481,100 @@ impl App {
                         })
                     )
                     .build();
-                let cfg = self.config.clone();
-                let keys_action = gio::ActionEntry::builder("buttons")
+
+                let comlynx_units_action = gio::ActionEntry::builder("comlynx_units")
+                    .parameter_type(Some(&i32::static_variant_type()))
+                    .state((self.config.units() as i32).to_variant())
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, action, param| {
+                            let units = param.unwrap().get::<i32>().unwrap();
+                            action.set_state(&units.to_variant());
+                            tx.send(Event::ComlynxUnits(units as u8)).unwrap();
+                        })
+                    )
+                    .build();
+
+                let unit_keys_action = gio::ActionEntry::builder("unit_buttons")
+                    .parameter_type(Some(&i32::static_variant_type()))
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, _, param| {
+                            let unit = param.unwrap().get::<i32>().unwrap();
+                            tx.send(Event::ButtonsMapping(unit as usize)).unwrap();
+                        })
+                    )
+                    .build();
+
+                let netplay_host_action = gio::ActionEntry::builder("netplay_host")
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, _, _| tx.send(Event::NetplaySettings(true)).unwrap()
+                    ))
+                    .build();
+
+                let netplay_join_action = gio::ActionEntry::builder("netplay_join")
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, _, _| tx.send(Event::NetplaySettings(false)).unwrap()
+                    ))
+                    .build();
+
+                let netplay_stop_action = gio::ActionEntry::builder("netplay_stop")
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, _, _| tx.send(Event::StopNetplay).unwrap()
+                    ))
+                    .build();
+
+                let serial_settings_action = gio::ActionEntry::builder("serial_settings")
                     .activate(clone!(
                         #[strong] tx,
-                        move |_, _, _| show_key_mapping_setter(tx.clone(), cfg.clone())
+                        move |_, _, _| tx.send(Event::SerialSettings).unwrap()
                     ))
                     .build();
 
-                app.add_action_entries([pause_action, mute_action, keys_action, load_rom_action]);
+                app.add_action_entries([pause_action, mute_action, load_rom_action, comlynx_units_action, unit_keys_action]);
+                app.add_action_entries([netplay_host_action, netplay_join_action, netplay_stop_action, serial_settings_action]);
                 app.set_accels_for_action("app.mute", &["<Alt>m"]);
                 app.set_accels_for_action("app.pause", &["<Alt>p"]);  
-                app.set_accels_for_action("app.buttons", &["<Alt>b"]);
                 app.set_accels_for_action("app.load_rom", &["<Alt>o"]);
 
+                let comlynx_menu = gio::Menu::new();
+                let units_menu = gio::Menu::new();
+                for units in 1..=MAX_COMLYNX_UNITS as i32 {
+                    let label = match units {
+                        1 => "_Off".to_string(),
+                        _ => format!("_{} units", units),
+                    };
+                    let item = gio::MenuItem::new(Some(&label), None);
+                    item.set_action_and_target_value(Some("app.comlynx_units"), Some(&units.to_variant()));
+                    units_menu.append_item(&item);
+                }
+                comlynx_menu.append_section(None, &units_menu);
+                let unit_keys_menu = gio::Menu::new();
+                for unit in 1..MAX_COMLYNX_UNITS as i32 {
+                    let item = gio::MenuItem::new(Some(&format!("Unit {} buttons", unit + 1)), None);
+                    item.set_action_and_target_value(Some("app.unit_buttons"), Some(&unit.to_variant()));
+                    unit_keys_menu.append_item(&item);
+                }
+                comlynx_menu.append_section(None, &unit_keys_menu);
+                let netplay_menu = gio::Menu::new();
+                netplay_menu.append_item(&gio::MenuItem::new(Some("_Host netplay"), Some("app.netplay_host")));
+                netplay_menu.append_item(&gio::MenuItem::new(Some("_Join netplay"), Some("app.netplay_join")));
+                netplay_menu.append_item(&gio::MenuItem::new(Some("_Stop netplay"), Some("app.netplay_stop")));
+                comlynx_menu.append_section(None, &netplay_menu);
+                let serial_menu = gio::Menu::new();
+                serial_menu.append_item(&gio::MenuItem::new(Some("Se_rial port"), Some("app.serial_settings")));
+                comlynx_menu.append_section(None, &serial_menu);
+
                 let settings_menu = gio::Menu::new();
                 settings_menu.append_item(&pause_menu_item);
                 settings_menu.append_item(&mute_state_item);
                 let keys_menu = gio::Menu::new();
                 keys_menu.append_item(&keys_menu_item);
+                keys_menu.append_item(&cheats_menu_item);
+                keys_menu.append_submenu(Some("Com_Lynx"), &comlynx_menu);
                 settings_menu.append_section(None, &keys_menu);
                 let reset_menu = gio::Menu::new();
                 reset_menu.append_item(&rom_menu_item);
@@ -284,6 +583,316 @@ impl App {
                 settings_menu
             };
     
+            let cartridge_menu = {
+                let cart_info_menu_item = gio::MenuItem::new(Some("Cartridge _info"), Some("app.cart_info"));
+                let import_eeprom_menu_item = gio::MenuItem::new(Some("I_mport EEPROM"), Some("app.import_eeprom"));
+                let export_eeprom_menu_item = gio::MenuItem::new(Some("_Export EEPROM"), Some("app.export_eeprom"));
+
+                let import_eeprom_action = gio::ActionEntry::builder("import_eeprom")
+                    .activate(clone!(
+                        #[strong] tx,
+                        #[weak] window,
+                        move |_, _, _| show_eeprom_picker(tx.clone(), &window, false)
+                    ))
+                    .build();
+
+                let export_eeprom_action = gio::ActionEntry::builder("export_eeprom")
+                    .activate(clone!(
+                        #[strong] tx,
+                        #[weak] window,
+                        move |_, _, _| show_eeprom_picker(tx.clone(), &window, true)
+                    ))
+                    .build();
+
+                let watch_cart_action = gio::ActionEntry::builder("watch_cart")
+                    .state(self.config.watch_cart().into())
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, action, _| {
+                            let checked = !action.state().unwrap().get::<bool>().unwrap();
+                            action.set_state(&checked.into());
+                            tx.send(Event::WatchCart(checked)).unwrap();
+                        })
+                    )
+                    .build();
+
+                let watch_restore_action = gio::ActionEntry::builder("watch_restore")
+                    .parameter_type(Some(&String::static_variant_type()))
+                    .state(format!("{:?}", self.config.watch_restore()).to_variant())
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, action, param| {
+                            let name = param.unwrap().get::<String>().unwrap();
+                            if let Some(restore) = WatchRestore::iter().find(|r| format!("{:?}", r) == name) {
+                                action.set_state(&name.to_variant());
+                                tx.send(Event::WatchRestore(restore)).unwrap();
+                            }
+                        })
+                    )
+                    .build();
+
+                let bookmark_state_action = gio::ActionEntry::builder("bookmark_state")
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, _, _| tx.send(Event::BookmarkState).unwrap()
+                    ))
+                    .build();
+
+                app.add_action_entries([import_eeprom_action, export_eeprom_action, watch_cart_action, watch_restore_action, bookmark_state_action]);
+                app.set_accels_for_action("app.watch_cart", &["<Alt>w"]);
+
+                let cartridge_menu = gio::Menu::new();
+                cartridge_menu.append_item(&cart_info_menu_item);
+                let eeprom_menu = gio::Menu::new();
+                eeprom_menu.append_item(&import_eeprom_menu_item);
+                eeprom_menu.append_item(&export_eeprom_menu_item);
+                cartridge_menu.append_section(None, &eeprom_menu);
+                let watch_restore_menu = gio::Menu::new();
+                for restore in WatchRestore::iter() {
+                    let item = gio::MenuItem::new(Some(restore.label()), None);
+                    item.set_action_and_target_value(Some("app.watch_restore"), Some(&format!("{:?}", restore).to_variant()));
+                    watch_restore_menu.append_item(&item);
+                }
+                let watch_menu = gio::Menu::new();
+                watch_menu.append_item(&gio::MenuItem::new(Some("_Watch cartridge file"), Some("app.watch_cart")));
+                watch_menu.append_submenu(Some("On _change"), &watch_restore_menu);
+                watch_menu.append_item(&gio::MenuItem::new(Some("_Bookmark state"), Some("app.bookmark_state")));
+                cartridge_menu.append_section(None, &watch_menu);
+                cartridge_menu
+            };
+
+            let debug_menu = {
+                let trace_menu_item = gio::MenuItem::new(Some("_Trace execution"), Some("app.trace"));
+                let trace_settings_menu_item = gio::MenuItem::new(Some("Trace _settings"), Some("app.trace_settings"));
+
+                let trace_action = gio::ActionEntry::builder("trace")
+                    .state(self.config.trace().into())
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, action, _| {
+                            let checked = !action.state().unwrap().get::<bool>().unwrap();
+                            action.set_state(&checked.into());
+                            tx.send(Event::Trace(checked)).unwrap();
+                        })
+                    )
+                    .build();
+
+                let suzy_inspector_menu_item = gio::MenuItem::new(Some("Suzy _inspector"), Some("app.suzy_inspector"));
+                let video_viewer_menu_item = gio::MenuItem::new(Some("_Palette and video buffers"), Some("app.video_viewer"));
+                let audio_inspector_menu_item = gio::MenuItem::new(Some("_Audio channels"), Some("app.audio_inspector"));
+                let profiler_menu_item = gio::MenuItem::new(Some("Pro_filer"), Some("app.profiler"));
+                let ram_search_menu_item = gio::MenuItem::new(Some("RAM _search"), Some("app.ram_search"));
+                let debug_snapshots_menu_item = gio::MenuItem::new(Some("_Live update"), Some("app.debug_snapshots"));
+
+                let debug_snapshots_action = gio::ActionEntry::builder("debug_snapshots")
+                    .state(self.config.debug_snapshots().into())
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, action, _| {
+                            let checked = !action.state().unwrap().get::<bool>().unwrap();
+                            action.set_state(&checked.into());
+                            tx.send(Event::DebugSnapshots(checked)).unwrap();
+                        })
+                    )
+                    .build();
+
+                app.add_action_entries([trace_action, debug_snapshots_action]);
+                app.set_accels_for_action("app.trace", &["<Alt>e"]);
+
+                let debug_menu = gio::Menu::new();
+                let trace_menu = gio::Menu::new();
+                trace_menu.append_item(&trace_menu_item);
+                trace_menu.append_item(&trace_settings_menu_item);
+                debug_menu.append_section(None, &trace_menu);
+                let views_menu = gio::Menu::new();
+                views_menu.append_item(&suzy_inspector_menu_item);
+                views_menu.append_item(&video_viewer_menu_item);
+                views_menu.append_item(&audio_inspector_menu_item);
+                views_menu.append_item(&debug_snapshots_menu_item);
+                debug_menu.append_section(None, &views_menu);
+                let profiler_menu = gio::Menu::new();
+                profiler_menu.append_item(&profiler_menu_item);
+                profiler_menu.append_item(&ram_search_menu_item);
+                debug_menu.append_section(None, &profiler_menu);
+                debug_menu
+            };
+
+            let video_menu = {
+                let ghosting_action = gio::ActionEntry::builder("ghosting")
+                    .parameter_type(Some(&i32::static_variant_type()))
+                    .state((self.config.ghosting() as i32).to_variant())
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, action, param| {
+                            let persistence = param.unwrap().get::<i32>().unwrap();
+                            action.set_state(&persistence.to_variant());
+                            tx.send(Event::Ghosting(persistence as u8)).unwrap();
+                        })
+                    )
+                    .build();
+
+                let scaler_action = gio::ActionEntry::builder("scaler")
+                    .parameter_type(Some(&String::static_variant_type()))
+                    .state(format!("{:?}", self.config.scaler()).to_variant())
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, action, param| {
+                            let name = param.unwrap().get::<String>().unwrap();
+                            if let Some(scaler) = Scaler::iter().find(|s| format!("{:?}", s) == name) {
+                                action.set_state(&name.to_variant());
+                                tx.send(Event::Scaler(scaler)).unwrap();
+                            }
+                        })
+                    )
+                    .build();
+
+                let lcd_grid_action = gio::ActionEntry::builder("lcd_grid")
+                    .state(self.config.lcd_grid().into())
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, action, _| {
+                            let checked = !action.state().unwrap().get::<bool>().unwrap();
+                            action.set_state(&checked.into());
+                            tx.send(Event::LcdGrid(checked)).unwrap();
+                        })
+                    )
+                    .build();
+
+                let window_scale_action = gio::ActionEntry::builder("window_scale")
+                    .parameter_type(Some(&i32::static_variant_type()))
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, _, param| {
+                            let scale = param.unwrap().get::<i32>().unwrap();
+                            tx.send(Event::WindowScale(scale as u8)).unwrap();
+                        })
+                    )
+                    .build();
+
+                let fullscreen_action = gio::ActionEntry::builder("fullscreen")
+                    .state(self.config.fullscreen().into())
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, action, _| {
+                            let checked = !action.state().unwrap().get::<bool>().unwrap();
+                            action.set_state(&checked.into());
+                            tx.send(Event::Fullscreen(checked)).unwrap();
+                        })
+                    )
+                    .build();
+
+                let renderer_action = gio::ActionEntry::builder("renderer")
+                    .parameter_type(Some(&String::static_variant_type()))
+                    .state(format!("{:?}", self.config.renderer()).to_variant())
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, action, param| {
+                            let name = param.unwrap().get::<String>().unwrap();
+                            if let Some(renderer) = Renderer::iter().find(|r| format!("{:?}", r) == name) {
+                                action.set_state(&name.to_variant());
+                                tx.send(Event::Renderer(renderer)).unwrap();
+                            }
+                        })
+                    )
+                    .build();
+
+                // An empty name selects no shader.
+                let shader_action = gio::ActionEntry::builder("shader")
+                    .parameter_type(Some(&String::static_variant_type()))
+                    .state(self.config.shader().unwrap_or_default().to_variant())
+                    .activate(clone!(
+                        #[strong] tx,
+                        move |_, action, param| {
+                            let name = param.unwrap().get::<String>().unwrap();
+                            action.set_state(&name.to_variant());
+                            tx.send(Event::Shader(Some(name).filter(|n| !n.is_empty()))).unwrap();
+                        })
+                    )
+                    .build();
+
+                let osd_actions: Vec<_> = OsdElement::iter()
+                    .map(|element| {
+                        gio::ActionEntry::builder(&osd_action_name(element))
+                            .state(self.config.osd(element).into())
+                            .activate(clone!(
+                                #[strong] tx,
+                                move |_, action, _| {
+                                    let checked = !action.state().unwrap().get::<bool>().unwrap();
+                                    action.set_state(&checked.into());
+                                    tx.send(Event::Osd(element, checked)).unwrap();
+                                })
+                            )
+                            .build()
+                    })
+                    .collect();
+
+                app.add_action_entries([ghosting_action, scaler_action, lcd_grid_action, window_scale_action, fullscreen_action, renderer_action, shader_action]);
+                app.add_action_entries(osd_actions);
+                app.set_accels_for_action("app.fullscreen", &["F11"]);
+
+                let window_size_menu = gio::Menu::new();
+                for scale in 1..=MAX_WINDOW_SCALE as i32 {
+                    let item = gio::MenuItem::new(Some(&format!("_{}x", scale)), None);
+                    item.set_action_and_target_value(Some("app.window_scale"), Some(&scale.to_variant()));
+                    window_size_menu.append_item(&item);
+                }
+
+                let scaler_menu = gio::Menu::new();
+                for scaler in Scaler::iter() {
+                    let item = gio::MenuItem::new(Some(scaler.label()), None);
+                    item.set_action_and_target_value(Some("app.scaler"), Some(&format!("{:?}", scaler).to_variant()));
+                    scaler_menu.append_item(&item);
+                }
+
+                let ghosting_menu = gio::Menu::new();
+                for (label, persistence) in [("_Off", 0), ("_Light", 25), ("_Medium", 50), ("_Heavy", 70)] {
+                    let item = gio::MenuItem::new(Some(label), None);
+                    item.set_action_and_target_value(Some("app.ghosting"), Some(&(persistence as i32).to_variant()));
+                    ghosting_menu.append_item(&item);
+                }
+
+                let renderer_menu = gio::Menu::new();
+                for renderer in Renderer::iter() {
+                    let item = gio::MenuItem::new(Some(renderer.label()), None);
+                    item.set_action_and_target_value(Some("app.renderer"), Some(&format!("{:?}", renderer).to_variant()));
+                    renderer_menu.append_item(&item);
+                }
+
+                let shader_menu = gio::Menu::new();
+                let item = gio::MenuItem::new(Some("_None"), None);
+                item.set_action_and_target_value(Some("app.shader"), Some(&"".to_variant()));
+                shader_menu.append_item(&item);
+                for shader in shaders() {
+                    // Underscores in file names aren't mnemonics.
+                    let item = gio::MenuItem::new(Some(&shader.replace('_', "__")), None);
+                    item.set_action_and_target_value(Some("app.shader"), Some(&shader.to_variant()));
+                    shader_menu.append_item(&item);
+                }
+
+                let osd_menu = gio::Menu::new();
+                for element in OsdElement::iter() {
+                    osd_menu.append_item(&gio::MenuItem::new(Some(element.label()), Some(&format!("app.{}", osd_action_name(element)))));
+                }
+
+                let video_menu = gio::Menu::new();
+                let size_menu = gio::Menu::new();
+                size_menu.append_item(&gio::MenuItem::new(Some("_Fullscreen"), Some("app.fullscreen")));
+                size_menu.append_submenu(Some("_Window size"), &window_size_menu);
+                video_menu.append_section(None, &size_menu);
+                let filters_menu = gio::Menu::new();
+                filters_menu.append_submenu(Some("_Renderer"), &renderer_menu);
+                filters_menu.append_submenu(Some("Sha_der"), &shader_menu);
+                filters_menu.append_submenu(Some("_Scaler"), &scaler_menu);
+                filters_menu.append_item(&gio::MenuItem::new(Some("LCD gr_id"), Some("app.lcd_grid")));
+                filters_menu.append_submenu(Some("LCD _ghosting"), &ghosting_menu);
+                filters_menu.append_item(&gio::MenuItem::new(Some("_Color correction"), Some("app.color_settings")));
+                video_menu.append_section(None, &filters_menu);
+                let osd_section = gio::Menu::new();
+                osd_section.append_submenu(Some("_On-screen display"), &osd_menu);
+                video_menu.append_section(None, &osd_section);
+                video_menu
+            };
+
             let help_menu = {
                 let about_menu_item = gio::MenuItem::new(Some("_About"), Some("app.about"));
     
@@ -294,7 +903,10 @@ impl App {
     
             let menubar = gio::Menu::new();
             menubar.append_submenu(Some("_File"), &file_menu);
+            menubar.append_submenu(Some("_Cartridge"), &cartridge_menu);
             menubar.append_submenu(Some("_Settings"), &settings_menu);
+            menubar.append_submenu(Some("_Video"), &video_menu);
+            menubar.append_submenu(Some("_Debug"), &debug_menu);
             menubar.append_submenu(Some("_Help"), &help_menu);
     
             menubar
@@ -338,13 +950,180 @@ impl App {
         self.update_config();
     }
 
-    pub fn reload_cart(&mut self) {
-        self.config.set_action(RunnerAction::LoadCart);
-        self.update_config();
+    /// Header of the loaded cart, editable when it is a plain `.lnx` file.
+    pub fn show_cart_info(&self) {
+        let Some(file) = self.config.cartridge().clone() else {
+            self.osd_message("No cartridge loaded".to_string());
+            return;
+        };
+        let data = match read_cart_file(&file, self.config.cartridge_entry()) {
+            Ok(data) => data,
+            Err(e) => {
+                error!("Couldn't read cart '{}'. '{}'", file.display(), e);
+                self.osd_message("Couldn't read the cartridge".to_string());
+                return;
+            }
+        };
+        let name = self.config.cartridge_entry().map(Path::new).unwrap_or(&file);
+        let (header, writable) = match LnxHeader::parse(&data) {
+            Some(header) => (header, matches!(archive_entries(&file), Ok(None))),
+            None if is_headerless(name) => (self.config.cart_header().cloned().unwrap_or_else(|| LnxHeader::detect(data.len())), false),
+            None => {
+                self.osd_message("The cartridge has no LNX header".to_string());
+                return;
+            }
+        };
+        show_cart_info(self.event_tx.clone(), &self.window, header, writable);
     }
 
-    pub fn load_cart(&mut self, file: PathBuf) {
-        self.config.set_cartridge(file);
+    /// Writes the edited header back to the `.lnx` file and reloads it.
+    pub fn write_cart_header(&mut self, header: LnxHeader) {
+        let Some(file) = self.config.cartridge().clone() else {
+            return;
+        };
+        let written = std::fs::read(&file).and_then(|data| match LnxHeader::parse(&data) {
+            Some(_) => std::fs::write(&file, header.lnx_image(&data[LNX_HEADER_SIZE..])),
+            None => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "No LNX header")),
+        });
+        match written {
+            Ok(()) => self.reload_cart(),
+            Err(e) => {
+                error!("Couldn't write the header of '{}'. '{}'", file.display(), e);
+                self.osd_message("Couldn't write the cartridge header".to_string());
+            }
+        }
+    }
+
+    pub fn watch_cart(&mut self, watch: bool) {
+        self.config.set_watch_cart(watch);
+        self.update_config();
+        self.update_cart_watcher();
+    }
+
+    pub fn watch_restore(&mut self, restore: WatchRestore) {
+        self.config.set_watch_restore(restore);
+        self.update_config();
+    }
+
+    /// Watches the loaded cart file when the option is on, following cart changes.
+    fn update_cart_watcher(&mut self) {
+        let cartridge = self.config.cartridge().clone().filter(|_| self.config.watch_cart());
+        if self.cart_watcher.as_ref().map(|w| w.file()) == cartridge.as_deref() {
+            return;
+        }
+        self.cart_watcher = cartridge.and_then(|file| CartWatcher::new(&file, self.event_tx.clone()));
+    }
+
+    /// Restarts the debounce delay, the cart is reloaded once the file stopped changing.
+    pub fn cart_file_changed(&mut self) {
+        if let Some(source) = self.watch_reload.take() {
+            source.remove();
+        }
+        let watch_reload = self.watch_reload.clone();
+        let tx = self.event_tx.clone();
+        self.watch_reload.replace(Some(glib::timeout_add_local_once(WATCH_DEBOUNCE, move || {
+            watch_reload.take();
+            tx.send(Event::WatchedCartChanged).unwrap();
+        })));
+    }
+
+    pub fn reload_watched_cart(&mut self) {
+        if !self.config.watch_cart() {
+            return;
+        }
+        self.reload_cart();
+        if self.config.watch_restore() == WatchRestore::Bookmark {
+            if let Some(file) = self.bookmark_file().filter(|file| file.exists()) {
+                self.load_state(file);
+            }
+        }
+    }
+
+    /// State restored after the watched cart is reloaded, saved by the runner like any other state.
+    pub fn bookmark_state(&mut self) {
+        match self.bookmark_file() {
+            Some(file) => self.save_state(file),
+            None => self.osd_message("Couldn't bookmark the state".to_string()),
+        }
+    }
+
+    /// Keyed by the cart file name, its content hash changes with every build.
+    fn bookmark_file(&self) -> Option<PathBuf> {
+        let name = self.config.cartridge().as_ref()?.file_name()?.to_string_lossy().to_string();
+        data_file("bookmarks", &format!("{}.sal", name))
+    }
+
+    pub fn import_eeprom(&mut self, file: PathBuf) {
+        self.config.set_action(RunnerAction::ImportEeprom(file));
+        self.update_config();
+    }
+
+    pub fn export_eeprom(&mut self, file: PathBuf) {
+        self.config.set_action(RunnerAction::ExportEeprom(file));
+        self.update_config();
+    }
+
+    pub fn reload_cart(&mut self) {
+        self.config.set_action(RunnerAction::ReloadCart);
+        self.update_config();
+    }
+
+    pub fn load_cart(&mut self, file: PathBuf) {
+        match archive_entries(&file) {
+            Ok(None) => self.load_cart_entry(file, None),
+            Ok(Some(entries)) => match entries.len() {
+                0 => self.osd_message("No cartridge in the archive".to_string()),
+                1 => self.load_cart_entry(file, entries.into_iter().next()),
+                _ => show_archive_chooser(self.event_tx.clone(), &self.window, file, entries),
+            },
+            Err(e) => {
+                error!("Couldn't read archive '{}'. '{}'", file.display(), e);
+                self.osd_message("Couldn't read the archive".to_string());
+            }
+        }
+    }
+
+    /// Reloads the cartridge with the IPS, BPS or UPS patch applied.
+    pub fn load_patch(&mut self, file: PathBuf) {
+        if self.config.cartridge().is_none() {
+            self.osd_message("Load a cartridge before applying a patch".to_string());
+            return;
+        }
+        self.config.set_patch(Some(file));
+        self.config.set_action(RunnerAction::ReloadCart);
+        self.update_config();
+    }
+
+    pub fn auto_patch(&mut self, auto_patch: bool) {
+        self.config.set_auto_patch(auto_patch);
+        self.update_config();
+    }
+
+    /// Loads a cartridge, `entry` being the file to extract when it is an archive.
+    /// Headerless dumps go through the layout dialog first.
+    pub fn load_cart_entry(&mut self, file: PathBuf, entry: Option<String>) {
+        let name = entry.as_deref().map(Path::new).unwrap_or(&file);
+        if !is_headerless(name) {
+            self.start_cart(file, entry, None);
+            return;
+        }
+        match read_cart_file(&file, entry.as_deref()) {
+            Ok(data) => show_headerless_settings(self.event_tx.clone(), &self.window, file, entry, data),
+            Err(e) => {
+                error!("Couldn't read cart '{}'. '{}'", file.display(), e);
+                self.osd_message("Couldn't read the cartridge".to_string());
+            }
+        }
+    }
+
+    pub fn load_headerless_cart(&mut self, file: PathBuf, entry: Option<String>, header: LnxHeader) {
+        self.start_cart(file, entry, Some(header));
+    }
+
+    fn start_cart(&mut self, file: PathBuf, entry: Option<String>, header: Option<LnxHeader>) {
+        self.config.set_cartridge(file);
+        self.config.set_cartridge_entry(entry);
+        self.config.set_cart_header(header);
         self.config.set_action(RunnerAction::LoadCart);
         self.update_config();
     }
@@ -369,9 +1148,432 @@ impl App {
         self.config.set_action(RunnerAction::SaveState(file));
         self.update_config();
     }
+
+    pub fn trace(&mut self, trace: bool) {
+        self.config.set_trace(trace);
+        self.update_config();
+    }
+
+    pub fn trace_stopped(&mut self) {
+        self.set_action_state("trace", false);
+        self.trace(false);
+    }
+
+    pub fn show_trace_settings(&self) {
+        show_trace_settings(self.event_tx.clone(), self.config.trace_settings());
+    }
+
+    pub fn set_trace_settings(&mut self, settings: TraceSettings) {
+        self.config.set_trace_settings(settings);
+        self.update_config();
+    }
+
+    pub fn debug_snapshot(&mut self, snapshot: &DebugSnapshot) {
+        self.debug_views.retain(|view| !view.is_closed());
+        for view in self.debug_views.iter_mut() {
+            view.update(snapshot);
+        }
+        if self.debug_views.is_empty() && self.config.debug_snapshots() {
+            self.set_action_state("debug_snapshots", false);
+            self.debug_snapshots(false);
+        }
+    }
+
+    pub fn debug_snapshots(&mut self, debug_snapshots: bool) {
+        self.config.set_debug_snapshots(debug_snapshots);
+        self.update_config();
+    }
+
+    pub fn request_snapshot(&mut self) {
+        self.config.set_action(RunnerAction::Snapshot);
+        self.update_config();
+    }
+
+    pub fn show_suzy_inspector(&mut self) {
+        self.debug_views.push(Box::new(SuzyInspector::new(self.event_tx.clone(), &self.display)));
+        self.request_snapshot();
+    }
+
+    pub fn show_video_viewer(&mut self) {
+        self.debug_views.push(Box::new(VideoViewer::new(self.event_tx.clone())));
+        self.request_snapshot();
+    }
+
+    pub fn show_audio_inspector(&mut self) {
+        self.debug_views.push(Box::new(AudioInspector::new(
+            self.event_tx.clone(),
+            self.config.muted_channels(),
+            self.config.solo_channels(),
+        )));
+        self.request_snapshot();
+    }
+
+    pub fn audio_channels(&mut self, muted: u8, solo: u8) {
+        self.config.set_channels(muted, solo);
+        self.update_config();
+    }
+
+    pub fn show_profiler(&mut self) {
+        if self.profiler_view.as_ref().is_some_and(|view| !view.is_closed()) {
+            return;
+        }
+        self.profiler_view = Some(ProfilerView::new(self.event_tx.clone()));
+    }
+
+    pub fn profile(&mut self, frames: u32) {
+        self.config.set_action(RunnerAction::Profile(frames));
+        self.update_config();
+    }
+
+    pub fn profile_report(&mut self, report: ProfileReport) {
+        if let Some(view) = self.profiler_view.as_mut().filter(|view| !view.is_closed()) {
+            view.update(report);
+        }
+    }
+
+    pub fn cart_loaded(&mut self, hash: u32, cartridge: PathBuf) {
+        // Reloading the running cart starts it afresh.
+        let resume = self.config.auto_resume() && self.cart_hash != Some(hash);
+        self.save_thumbnail();
+        self.cart_hash = Some(hash);
+        if self.config.cartridge().as_ref() != Some(&cartridge) {
+            self.config.set_cartridge(cartridge.clone());
+        }
+        self.config.add_recent_cart(cartridge);
+        self.refresh_recent_menu();
+        self.update_cart_watcher();
+        self.play_history.played(hash);
+        if let Some(library) = self.library.as_ref().filter(|l| !l.is_closed()) {
+            library.set_history(self.play_history.clone());
+        }
+        let cheats = data_file("cheats", &cart_file_name(hash, "cht"))
+            .map(|file| load_cheats(&file))
+            .unwrap_or_default();
+        self.config.set_cheats(cheats.clone());
+        self.update_config();
+        if let Some(manager) = self.cheat_manager.as_ref().filter(|m| !m.is_closed()) {
+            manager.set_cheats(cheats);
+        }
+        if let Some(file) = resume_file(hash).filter(|file| resume && file.exists()) {
+            self.load_state(file);
+        }
+    }
+
+    fn refresh_recent_menu(&self) {
+        self.recent_menu.remove_all();
+        let carts = gio::Menu::new();
+        for (i, cart) in self.config.recent_carts().iter().enumerate() {
+            let name = cart.file_name().map(|name| name.to_string_lossy().replace('_', "__")).unwrap_or_default();
+            let item = gio::MenuItem::new(Some(&format!("_{} {}", (i + 1) % 10, name)), None);
+            item.set_action_and_target_value(Some("app.recent"), Some(&cart.to_string_lossy().to_variant()));
+            carts.append_item(&item);
+        }
+        self.recent_menu.append_section(None, &carts);
+        let clear = gio::Menu::new();
+        clear.append(Some("_Clear"), Some("app.clear_recent"));
+        self.recent_menu.append_section(None, &clear);
+    }
+
+    pub fn clear_recent_carts(&mut self) {
+        self.config.clear_recent_carts();
+        self.refresh_recent_menu();
+        self.update_config();
+    }
+
+    pub fn auto_resume(&mut self, auto_resume: bool) {
+        self.config.set_auto_resume(auto_resume);
+        self.update_config();
+    }
+
+    /// Stops the runner, saving the state of the running cart first when auto-resume is on.
+    pub fn shutdown(&mut self) {
+        if self.config.auto_resume() {
+            if let Some(file) = self.cart_hash.and_then(resume_file) {
+                self.config.set_action(RunnerAction::SaveState(file));
+                self.update_config();
+            }
+        }
+        self.config.set_action(RunnerAction::Shutdown);
+        self.update_config();
+        self.runner.join();
+    }
+
+    pub fn show_library(&mut self) {
+        if self.library.as_ref().is_some_and(|l| !l.is_closed()) {
+            return;
+        }
+        self.library = Some(Library::new(self.event_tx.clone(), self.config.library_folders().clone(), self.play_history.clone()));
+        scan(self.config.library_folders().clone(), self.event_tx.clone());
+    }
+
+    pub fn library_folders(&mut self, folders: Vec<PathBuf>) {
+        self.config.set_library_folders(folders.clone());
+        self.update_config();
+        if let Some(library) = self.library.as_ref().filter(|l| !l.is_closed()) {
+            library.set_folders(folders.clone());
+            scan(folders, self.event_tx.clone());
+        }
+    }
+
+    pub fn library_scanned(&mut self, carts: Vec<CartInfo>) {
+        if let Some(library) = self.library.as_ref().filter(|l| !l.is_closed()) {
+            library.set_carts(carts);
+        }
+    }
+
+    /// Keeps the current screen of the running cart for the library.
+    pub fn save_thumbnail(&self) {
+        let Some(file) = self.cart_hash.and_then(thumbnail_file) else {
+            return;
+        };
+        if let Err(e) = self.display.save_screenshot(&file) {
+            error!("Couldn't save thumbnail. '{}'", e);
+        }
+    }
+
+    pub fn show_cheat_manager(&mut self) {
+        if self.cheat_manager.as_ref().is_some_and(|m| !m.is_closed()) {
+            return;
+        }
+        self.cheat_manager = Some(CheatManager::new(self.event_tx.clone(), self.config.cheats().clone()));
+    }
+
+    pub fn show_ram_search(&mut self) {
+        self.debug_views.push(Box::new(RamSearch::new(self.event_tx.clone())));
+    }
+
+    pub fn add_cheat(&mut self, cheat: Cheat) {
+        let mut cheats = self.config.cheats().clone();
+        cheats.push(cheat);
+        self.update_cheats(cheats);
+    }
+
+    pub fn update_cheats(&mut self, cheats: Vec<Cheat>) {
+        if let Some(file) = self.cart_hash.and_then(|hash| data_file("cheats", &cart_file_name(hash, "cht"))) {
+            if let Err(e) = save_cheats(&file, &cheats) {
+                error!("Couldn't save cheats. '{}'", e);
+            }
+        }
+        self.config.set_cheats(cheats.clone());
+        self.update_config();
+        if let Some(manager) = self.cheat_manager.as_ref().filter(|m| !m.is_closed()) {
+            manager.set_cheats(cheats);
+        }
+    }
+
+    pub fn show_key_mapping(&self, unit: usize) {
+        show_key_mapping_setter(self.event_tx.clone(), self.config.clone(), unit);
+    }
+
+    pub fn ghosting(&mut self, persistence: u8) {
+        self.config.set_ghosting(persistence);
+        for display in self.displays() {
+            self.configure_display(display);
+        }
+        self.update_config();
+    }
+
+    pub fn scaler(&mut self, scaler: Scaler) {
+        self.config.set_scaler(scaler);
+        for display in self.displays() {
+            self.configure_display(display);
+        }
+        self.update_config();
+    }
+
+    pub fn lcd_grid(&mut self, lcd_grid: bool) {
+        self.config.set_lcd_grid(lcd_grid);
+        for display in self.displays() {
+            self.configure_display(display);
+        }
+        self.update_config();
+    }
+
+    pub fn renderer(&mut self, renderer: Renderer) {
+        self.config.set_renderer(renderer);
+        self.gl_failed = false;
+        self.layout_tiles();
+        self.update_config();
+    }
+
+    pub fn shader(&mut self, shader: Option<String>) {
+        self.config.set_shader(shader);
+        if self.config.renderer() == Renderer::Gl {
+            self.layout_tiles();
+        }
+        self.update_config();
+    }
+
+    /// Falls back to the software renderer, the setting is kept to retry on the next run.
+    pub fn renderer_failed(&mut self, error: String) {
+        if self.gl_failed {
+            return;
+        }
+        error!("Couldn't start the OpenGL renderer. '{}'", error);
+        self.gl_failed = true;
+        self.layout_tiles();
+        self.osd_message("OpenGL unavailable, using the software renderer".to_string());
+    }
+
+    pub fn show_color_settings(&self) {
+        show_color_settings(self.event_tx.clone(), self.config.color_profile(), self.config.color_adjustments());
+    }
+
+    pub fn color_correction(&mut self, profile: ColorProfile, adjustments: ColorAdjustments) {
+        self.config.set_color_profile(profile);
+        self.config.set_color_adjustments(adjustments);
+        for display in self.displays() {
+            self.configure_display(display);
+        }
+        self.update_config();
+    }
+
+    pub fn osd(&mut self, element: OsdElement, shown: bool) {
+        self.config.set_osd(element, shown);
+        for display in self.displays() {
+            self.configure_display(display);
+        }
+        self.update_config();
+    }
+
+    pub fn runner_stats(&self, stats: RunnerStats) {
+        self.display.set_stats(stats);
+    }
+
+    pub fn osd_message(&self, message: String) {
+        self.display.show_message(message);
+    }
+
+    /// Resizes the window so every unit screen is shown at `scale` times the Lynx resolution.
+    pub fn window_scale(&mut self, scale: u8) {
+        let units = self.config.units();
+        let columns = (units as f64).sqrt().ceil() as i32;
+        let rows = (units as i32 + columns - 1) / columns;
+        let width = LYNX_SCREEN_WIDTH as i32 * scale as i32 * columns;
+        let height = LYNX_SCREEN_HEIGHT as i32 * scale as i32 * rows;
+
+        // Menubar and decorations around the screens.
+        let extra_width = (self.window.width() - self.tiles.width()).max(0);
+        let extra_height = (self.window.height() - self.tiles.height()).max(0);
+
+        self.window.unmaximize();
+        self.window.set_default_size(width + extra_width, height + extra_height);
+    }
+
+    pub fn fullscreen(&mut self, fullscreen: bool) {
+        match fullscreen {
+            true => self.window.fullscreen(),
+            false => self.window.unfullscreen(),
+        }
+    }
+
+    pub fn save_window_state(&mut self) {
+        if self.window.is_fullscreen() {
+            // Keep the windowed size stored by the previous run.
+            return;
+        }
+        self.config.set_window_maximized(self.window.is_maximized());
+        if !self.window.is_maximized() {
+            let (width, height) = self.window.default_size();
+            self.config.set_window_size(width, height);
+        }
+        self.update_config();
+    }
+
+    fn set_unit_count(&mut self, units: u8) {
+        self.config.set_comlynx(units > 1);
+        self.config.set_comlynx_units(units);
+        self.layout_tiles();
+        if let Some(action) = self.application.lookup_action("comlynx_units") {
+            action.change_state(&(units as i32).to_variant());
+        }
+    }
+
+    pub fn comlynx_units(&mut self, units: u8) {
+        self.set_unit_count(units);
+        self.update_config();
+    }
+
+    pub fn show_netplay_settings(&self, host: bool) {
+        show_netplay_settings(self.event_tx.clone(), &self.config, host);
+    }
+
+    fn start_netplay(&mut self, action: RunnerAction) {
+        self.config.set_netplay(true);
+        self.layout_tiles();
+        self.config.set_action(action);
+        self.update_config();
+    }
+
+    pub fn host_netplay(&mut self, address: String) {
+        self.config.set_netplay_listen_address(address.clone());
+        self.start_netplay(RunnerAction::HostNetplay(address));
+    }
+
+    pub fn join_netplay(&mut self, address: String) {
+        self.config.set_netplay_address(address.clone());
+        self.start_netplay(RunnerAction::JoinNetplay(address));
+    }
+
+    pub fn stop_netplay(&mut self) {
+        self.config.set_netplay(false);
+        self.layout_tiles();
+        self.config.set_action(RunnerAction::StopNetplay);
+        self.update_config();
+    }
+
+    pub fn netplay_status(&self, status: String) {
+        self.osd_message(status);
+    }
+
+    pub fn show_serial_settings(&self) {
+        show_serial_settings(self.event_tx.clone(), self.config.serial_backing());
+    }
+
+    pub fn serial_backing(&mut self, backing: SerialBacking) {
+        self.config.set_serial_backing(backing);
+        self.update_config();
+    }
+
+    pub fn serial_status(&self, status: String) {
+        self.osd_message(status);
+    }
+
+    fn set_action_state(&self, name: &str, state: bool) {
+        if let Some(action) = self.application.lookup_action(name) {
+            action.downcast::<gio::SimpleAction>().unwrap().set_state(&state.into());
+        }
+    }
+}
+
+/// State saved on quit for auto-resume.
+fn resume_file(cart_hash: u32) -> Option<PathBuf> {
+    data_file("resume", &cart_file_name(cart_hash, "sal"))
+}
+
+fn display_picture(display: &LynxDisplay) -> gtk::Picture {
+    let picture = gtk::Picture::builder()
+        .paintable(display)
+        .hexpand(true)
+        .vexpand(true)
+        .halign(gtk::Align::Fill)
+        .valign(gtk::Align::Fill)
+        .content_fit(gtk::ContentFit::Contain)
+        .can_shrink(true)
+        .build();
+    display.set_pango_context(&picture.pango_context());
+    picture
 }
 
-fn show_key_mapping_setter(event_tx: kanal::Sender<Event>, config: RunnerConfig) {
+fn osd_action_name(element: OsdElement) -> String {
+    format!("osd_{:?}", element).to_lowercase()
+}
+
+const UNMAPPED_KEY: &str = "-";
+
+fn show_key_mapping_setter(event_tx: kanal::Sender<Event>, config: RunnerConfig, unit: usize) {
+    let mapping = config.unit_button_mapping(unit).cloned().unwrap_or_default();
+
     let grid = gtk::Grid::builder()
         .margin_start(6).margin_end(6).margin_top(6).margin_bottom(6)
         .halign(gtk::Align::Start).valign(gtk::Align::Center)
@@ -383,8 +1585,11 @@ fn show_key_mapping_setter(event_tx: kanal::Sender<Event>, config: RunnerConfig)
         let label = gtk::Label::new(Some(input.to_string().as_str()));
         grid.attach(&label, 0, i as i32, 1, 1);
 
-        let prev_key = config.button_mapping().iter().find(|(_, v)| **v == input).unwrap().0;
-        let btn = gtk::Button::with_label(prev_key);
+        let prev_key = mapping
+            .iter()
+            .find(|(_, v)| **v == input)
+            .map_or(UNMAPPED_KEY.to_string(), |(k, _)| k.clone());
+        let btn = gtk::Button::with_label(&prev_key);
         unsafe { 
             btn.set_data("input", input.to_string());
             btn.set_data("key",prev_key.to_string());
@@ -457,7 +1662,10 @@ fn show_key_mapping_setter(event_tx: kanal::Sender<Event>, config: RunnerConfig)
     
     let win = ApplicationWindow::builder()
         .modal(true)
-        .title("Buttons")
+        .title(match unit {
+            0 => "Buttons".to_string(),
+            _ => format!("Unit {} buttons", unit + 1),
+        })
         .child(&grid)
         .build(); 
 
@@ -477,7 +1685,9 @@ fn show_key_mapping_setter(event_tx: kanal::Sender<Event>, config: RunnerConfig)
                     let skey = child.data::<String>("key").unwrap().read(); 
                     let sinput = child.data::<String>("input").unwrap().read(); 
                     let input = Input::iter().find(|i| i.to_string() == sinput).unwrap();
-                    mut_conf.set_button_mapping_as_str(skey, input);
+                    if skey != UNMAPPED_KEY {
+                        mut_conf.set_unit_button_mapping_as_str(unit, skey, input);
+                    }
                 };
             }
             event_tx.send(Event::UpdateConfig(mut_conf)).unwrap();
@@ -488,10 +1698,340 @@ fn show_key_mapping_setter(event_tx: kanal::Sender<Event>, config: RunnerConfig)
     win.present();
 }
 
-fn show_rom_picker(event_tx: kanal::Sender<Event>, window: &ApplicationWindow) {
+fn show_netplay_settings(event_tx: kanal::Sender<Event>, config: &RunnerConfig, host: bool) {
+    let grid = gtk::Grid::builder()
+        .margin_start(6).margin_end(6).margin_top(6).margin_bottom(6)
+        .halign(gtk::Align::Start).valign(gtk::Align::Center)
+        .row_spacing(6).column_spacing(6)
+        .build();
 
-    let filedialog = gtk::FileDialog::builder()
-        .title("Load ROM")
+    let entry = gtk::Entry::builder()
+        .text(match host {
+            true => config.netplay_listen_address().to_string(),
+            false => config.netplay_address().to_string(),
+        })
+        .hexpand(true)
+        .build();
+    grid.attach(&gtk::Label::new(Some(if host { "Listen address" } else { "Host address" })), 0, 0, 1, 1);
+    grid.attach(&entry, 1, 0, 1, 1);
+
+    let btn_ok = gtk::Button::with_label(if host { "Host" } else { "Join" });
+    grid.attach(&btn_ok, 0, 1, 1, 1);
+
+    let btn_cancel = gtk::Button::with_label("Cancel");
+    grid.attach(&btn_cancel, 1, 1, 1, 1);
+
+    let win = ApplicationWindow::builder()
+        .modal(true)
+        .title(if host { "Host netplay" } else { "Join netplay" })
+        .child(&grid)
+        .build();
+
+    btn_cancel.connect_clicked(clone!(
+        #[weak] win,
+        move |_| win.close()
+    ));
+
+    btn_ok.connect_clicked(clone!(
+        #[weak] win,
+        move |_| {
+            let text = entry.text().trim().to_string();
+            let event = match host {
+                true => Event::HostNetplay(text),
+                false => Event::JoinNetplay(text),
+            };
+            event_tx.send(event).unwrap();
+            win.close();
+        }
+    ));
+
+    win.present();
+}
+
+fn show_serial_settings(event_tx: kanal::Sender<Event>, backing: &SerialBacking) {
+    let grid = gtk::Grid::builder()
+        .margin_start(6).margin_end(6).margin_top(6).margin_bottom(6)
+        .halign(gtk::Align::Start).valign(gtk::Align::Center)
+        .row_spacing(6).column_spacing(6)
+        .build();
+
+    let kind = gtk::DropDown::from_strings(&["Off", "Pseudo-terminal", "Serial device"]);
+    kind.set_selected(match backing {
+        SerialBacking::Off => 0,
+        SerialBacking::Pty => 1,
+        SerialBacking::Device(_) => 2,
+    });
+    grid.attach(&gtk::Label::new(Some("ComLynx")), 0, 0, 1, 1);
+    grid.attach(&kind, 1, 0, 1, 1);
+
+    let device_entry = gtk::Entry::builder()
+        .placeholder_text("/dev/ttyUSB0")
+        .hexpand(true)
+        .build();
+    if let SerialBacking::Device(path) = backing {
+        device_entry.set_text(&path.to_string_lossy());
+    }
+    grid.attach(&gtk::Label::new(Some("Device")), 0, 1, 1, 1);
+    grid.attach(&device_entry, 1, 1, 1, 1);
+
+    let btn_ok = gtk::Button::with_label("OK");
+    grid.attach(&btn_ok, 0, 2, 1, 1);
+
+    let btn_cancel = gtk::Button::with_label("Cancel");
+    grid.attach(&btn_cancel, 1, 2, 1, 1);
+
+    let win = ApplicationWindow::builder()
+        .modal(true)
+        .title("ComLynx serial port")
+        .child(&grid)
+        .build();
+
+    btn_cancel.connect_clicked(clone!(
+        #[weak] win,
+        move |_| win.close()
+    ));
+
+    btn_ok.connect_clicked(clone!(
+        #[weak] win,
+        move |_| {
+            let backing = match kind.selected() {
+                1 => SerialBacking::Pty,
+                2 => SerialBacking::Device(PathBuf::from(device_entry.text().trim())),
+                _ => SerialBacking::Off,
+            };
+            event_tx.send(Event::SerialBacking(backing)).unwrap();
+            win.close();
+        }
+    ));
+
+    win.present();
+}
+
+/// Color profile selection, every change is previewed on the screen and reverted on cancel.
+fn show_color_settings(event_tx: kanal::Sender<Event>, profile: ColorProfile, adjustments: ColorAdjustments) {
+    let grid = gtk::Grid::builder()
+        .margin_start(6).margin_end(6).margin_top(6).margin_bottom(6)
+        .halign(gtk::Align::Fill).valign(gtk::Align::Center)
+        .row_spacing(6).column_spacing(6)
+        .build();
+
+    let profiles: Vec<ColorProfile> = ColorProfile::iter().collect();
+    let labels: Vec<&str> = profiles.iter().map(|p| p.label()).collect();
+    let profile_dropdown = gtk::DropDown::from_strings(&labels);
+    profile_dropdown.set_selected(profiles.iter().position(|p| *p == profile).unwrap_or_default() as u32);
+    grid.attach(&gtk::Label::new(Some("Profile")), 0, 0, 1, 1);
+    grid.attach(&profile_dropdown, 1, 0, 1, 1);
+
+    let slider = |row: i32, label: &str, min: f64, max: f64, value: f32| {
+        let scale = gtk::Scale::with_range(gtk::Orientation::Horizontal, min, max, 0.01);
+        scale.set_value(value as f64);
+        scale.set_digits(2);
+        scale.set_draw_value(true);
+        scale.set_hexpand(true);
+        scale.set_width_request(200);
+        grid.attach(&gtk::Label::new(Some(label)), 0, row, 1, 1);
+        grid.attach(&scale, 1, row, 1, 1);
+        scale
+    };
+    let gamma = slider(1, "Gamma", 0.5, 2.5, adjustments.gamma);
+    let brightness = slider(2, "Brightness", -0.5, 0.5, adjustments.brightness);
+    let contrast = slider(3, "Contrast", 0.5, 1.5, adjustments.contrast);
+    let saturation = slider(4, "Saturation", 0., 2., adjustments.saturation);
+    let sliders = [gamma.clone(), brightness.clone(), contrast.clone(), saturation.clone()];
+
+    let btn_reset = gtk::Button::with_label("Defaults");
+    grid.attach(&btn_reset, 0, 5, 2, 1);
+
+    let btn_ok = gtk::Button::with_label("OK");
+    grid.attach(&btn_ok, 0, 6, 1, 1);
+
+    let btn_cancel = gtk::Button::with_label("Cancel");
+    grid.attach(&btn_cancel, 1, 6, 1, 1);
+
+    let win = ApplicationWindow::builder()
+        .modal(true)
+        .title("Color correction")
+        .child(&grid)
+        .build();
+
+    let selection = Rc::new(clone!(
+        #[strong] profile_dropdown,
+        #[strong] gamma,
+        #[strong] brightness,
+        #[strong] contrast,
+        #[strong] saturation,
+        move || {
+            let profile = profiles[profile_dropdown.selected() as usize];
+            let adjustments = ColorAdjustments {
+                gamma: gamma.value() as f32,
+                brightness: brightness.value() as f32,
+                contrast: contrast.value() as f32,
+                saturation: saturation.value() as f32,
+            };
+            (profile, adjustments)
+        }
+    ));
+
+    let preview = Rc::new(clone!(
+        #[strong] event_tx,
+        #[strong] selection,
+        #[strong] sliders,
+        move || {
+            let (profile, adjustments) = selection();
+            for slider in &sliders {
+                slider.set_sensitive(profile == ColorProfile::Custom);
+            }
+            event_tx.send(Event::ColorCorrection(profile, adjustments)).unwrap();
+        }
+    ));
+    preview();
+
+    profile_dropdown.connect_selected_notify(clone!(
+        #[strong] preview,
+        move |_| preview()
+    ));
+    for slider in &sliders {
+        slider.connect_value_changed(clone!(
+            #[strong] preview,
+            move |_| preview()
+        ));
+    }
+
+    btn_reset.connect_clicked(move |_| {
+        let defaults = ColorAdjustments::default();
+        gamma.set_value(defaults.gamma as f64);
+        brightness.set_value(defaults.brightness as f64);
+        contrast.set_value(defaults.contrast as f64);
+        saturation.set_value(defaults.saturation as f64);
+    });
+
+    // Closing the window without OK restores the settings it was opened with.
+    let confirmed = Rc::new(RefCell::new(false));
+    win.connect_close_request(clone!(
+        #[strong] event_tx,
+        #[strong] confirmed,
+        move |_| {
+            if !*confirmed.borrow() {
+                event_tx.send(Event::ColorCorrection(profile, adjustments)).unwrap();
+            }
+            glib::Propagation::Proceed
+        }
+    ));
+
+    btn_cancel.connect_clicked(clone!(
+        #[weak] win,
+        move |_| win.close()
+    ));
+
+    btn_ok.connect_clicked(clone!(
+        #[weak] win,
+        move |_| {
+            let (profile, adjustments) = selection();
+            event_tx.send(Event::ColorCorrection(profile, adjustments)).unwrap();
+            *confirmed.borrow_mut() = true;
+            win.close();
+        }
+    ));
+
+    win.present();
+}
+
+fn parse_address(text: &str) -> Option<u16> {
+    u16::from_str_radix(text.trim().trim_start_matches('$').trim_start_matches("0x"), 16).ok()
+}
+
+fn show_trace_settings(event_tx: kanal::Sender<Event>, settings: &TraceSettings) {
+    let grid = gtk::Grid::builder()
+        .margin_start(6).margin_end(6).margin_top(6).margin_bottom(6)
+        .halign(gtk::Align::Start).valign(gtk::Align::Center)
+        .row_spacing(6).column_spacing(6)
+        .build();
+
+    grid.attach(&gtk::Label::new(Some("File")), 0, 0, 1, 1);
+    let file_entry = gtk::Entry::builder()
+        .text(&*settings.file().to_string_lossy())
+        .hexpand(true)
+        .build();
+    grid.attach(&file_entry, 1, 0, 1, 1);
+    let file_btn = gtk::Button::with_label("...");
+    grid.attach(&file_btn, 2, 0, 1, 1);
+
+    grid.attach(&gtk::Label::new(Some("Start address")), 0, 1, 1, 1);
+    let start_entry = gtk::Entry::builder()
+        .text(format!("{:04X}", settings.range_start()))
+        .max_length(4)
+        .build();
+    grid.attach(&start_entry, 1, 1, 2, 1);
+
+    grid.attach(&gtk::Label::new(Some("End address")), 0, 2, 1, 1);
+    let end_entry = gtk::Entry::builder()
+        .text(format!("{:04X}", settings.range_end()))
+        .max_length(4)
+        .build();
+    grid.attach(&end_entry, 1, 2, 2, 1);
+
+    grid.attach(&gtk::Label::new(Some("Frames (0: no limit)")), 0, 3, 1, 1);
+    let frames_spin = gtk::SpinButton::with_range(0., 100_000., 1.);
+    frames_spin.set_value(settings.frame_limit() as f64);
+    grid.attach(&frames_spin, 1, 3, 2, 1);
+
+    let btn_ok = gtk::Button::with_label("OK");
+    grid.attach(&btn_ok, 0, 4, 1, 1);
+
+    let btn_cancel = gtk::Button::with_label("Cancel");
+    grid.attach(&btn_cancel, 1, 4, 2, 1);
+
+    let win = ApplicationWindow::builder()
+        .modal(true)
+        .title("Trace settings")
+        .child(&grid)
+        .build();
+
+    file_btn.connect_clicked(clone!(
+        #[weak] win,
+        #[weak] file_entry,
+        move |_| {
+            let filedialog = gtk::FileDialog::builder()
+                .title("Trace file")
+                .modal(true)
+                .build();
+            filedialog.save(Some(&win), gio::Cancellable::NONE, move |file| {
+                if let Ok(file) = file {
+                    let filename = file.path().expect("Couldn't get file path");
+                    file_entry.set_text(&filename.to_string_lossy());
+                }
+            });
+        }
+    ));
+
+    btn_cancel.connect_clicked(clone!(
+        #[weak] win,
+        move |_| win.close()
+    ));
+
+    btn_ok.connect_clicked(clone!(
+        #[weak] win,
+        move |_| {
+            let (Some(start), Some(end)) = (parse_address(&start_entry.text()), parse_address(&end_entry.text())) else {
+                error!("Invalid trace address range.");
+                return;
+            };
+            let mut trace_settings = TraceSettings::default();
+            trace_settings.set_file(PathBuf::from(file_entry.text().as_str()));
+            trace_settings.set_range(start, end);
+            trace_settings.set_frame_limit(frames_spin.value_as_int() as u32);
+
+            event_tx.send(Event::SetTraceSettings(trace_settings)).unwrap();
+            win.close();
+        }
+    ));
+
+    win.present();
+}
+
+fn show_rom_picker(event_tx: kanal::Sender<Event>, window: &ApplicationWindow) {
+    let filedialog = gtk::FileDialog::builder()
+        .title("Load ROM")
         .modal(true)
         .build();
 
@@ -507,6 +2047,13 @@ fn show_rom_picker(event_tx: kanal::Sender<Event>, window: &ApplicationWindow) {
 fn show_cart_picker(event_tx: kanal::Sender<Event>, window: &ApplicationWindow) {
     let filters = gio::ListStore::new::<gtk::FileFilter>();
 
+    let all_filter = gtk::FileFilter::new();
+    for suffix in CART_EXTENSIONS.iter().chain(HEADERLESS_EXTENSIONS.iter()).chain(ARCHIVE_EXTENSIONS.iter()) {
+        all_filter.add_suffix(suffix);
+    }
+    all_filter.set_name(Some("Cartridges"));
+    filters.append(&all_filter);
+
     let lnx_filter = gtk::FileFilter::new();
     lnx_filter.add_suffix("lnx");
     lnx_filter.set_name(Some("lnx"));
@@ -517,6 +2064,20 @@ fn show_cart_picker(event_tx: kanal::Sender<Event>, window: &ApplicationWindow)
     o_filter.set_name(Some("o"));
     filters.append(&o_filter);
 
+    let headerless_filter = gtk::FileFilter::new();
+    for suffix in HEADERLESS_EXTENSIONS {
+        headerless_filter.add_suffix(suffix);
+    }
+    headerless_filter.set_name(Some("Headerless dumps"));
+    filters.append(&headerless_filter);
+
+    let archive_filter = gtk::FileFilter::new();
+    for suffix in ARCHIVE_EXTENSIONS {
+        archive_filter.add_suffix(suffix);
+    }
+    archive_filter.set_name(Some("Archives"));
+    filters.append(&archive_filter);
+
     let filedialog = gtk::FileDialog::builder()
         .title("Load cart")
         .modal(true)
@@ -532,6 +2093,306 @@ fn show_cart_picker(event_tx: kanal::Sender<Event>, window: &ApplicationWindow)
     });
 }
 
+fn show_patch_picker(event_tx: kanal::Sender<Event>, window: &ApplicationWindow) {
+    let filters = gio::ListStore::new::<gtk::FileFilter>();
+
+    let patch_filter = gtk::FileFilter::new();
+    for suffix in PATCH_EXTENSIONS {
+        patch_filter.add_suffix(suffix);
+    }
+    patch_filter.set_name(Some("Patches"));
+    filters.append(&patch_filter);
+
+    let filedialog = gtk::FileDialog::builder()
+        .title("Apply patch")
+        .modal(true)
+        .filters(&filters)
+        .build();
+
+    let txc = event_tx.clone();
+    filedialog.open(Some(window), gio::Cancellable::NONE, move |file| {
+        if let Ok(file) = file {
+            let filename = file.path().expect("Couldn't get file path");
+            txc.send(Event::LoadPatch(filename)).unwrap();
+        }
+    });
+}
+
+/// Drop down listing the bank sizes, `None` included for bank 1. A non standard size read from a
+/// header is kept as an extra entry. Returns the page size of each entry along.
+fn bank_dropdown(page_size: u16, allow_none: bool) -> (gtk::DropDown, Vec<u16>) {
+    let mut page_sizes: Vec<u16> = allow_none.then_some(0).into_iter().chain(BANK_PAGE_SIZES).collect();
+    if !page_sizes.contains(&page_size) {
+        page_sizes.push(page_size);
+    }
+    let labels: Vec<String> = page_sizes
+        .iter()
+        .map(|page_size| match page_size {
+            0 => "None".to_string(),
+            page_size => format!("{}K", *page_size as u32 / 4),
+        })
+        .collect();
+    let labels: Vec<&str> = labels.iter().map(|label| label.as_str()).collect();
+    let dropdown = gtk::DropDown::from_strings(&labels);
+    dropdown.set_selected(page_sizes.iter().position(|s| *s == page_size).unwrap_or(0) as u32);
+    (dropdown, page_sizes)
+}
+
+/// Bank sizes and rotation of a headerless dump, prefilled from its size. The cart can be
+/// loaded with them or saved as a `.lnx` file.
+fn show_headerless_settings(event_tx: kanal::Sender<Event>, parent: &ApplicationWindow, file: PathBuf, entry: Option<String>, data: Vec<u8>) {
+    let detected = LnxHeader::detect(data.len());
+    let name = entry.as_deref().map(Path::new).unwrap_or(&file);
+    let stem = name.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+
+    let grid = gtk::Grid::builder()
+        .margin_start(6).margin_end(6).margin_top(6).margin_bottom(6)
+        .halign(gtk::Align::Start).valign(gtk::Align::Center)
+        .row_spacing(6).column_spacing(6)
+        .build();
+
+    grid.attach(&gtk::Label::new(Some("Size")), 0, 0, 1, 1);
+    grid.attach(&gtk::Label::new(Some(&format!("{} bytes", data.len()))), 1, 0, 2, 1);
+
+    let (bank0, bank0_sizes) = bank_dropdown(detected.bank0_page_size, false);
+    grid.attach(&gtk::Label::new(Some("Bank 0")), 0, 1, 1, 1);
+    grid.attach(&bank0, 1, 1, 2, 1);
+
+    let (bank1, bank1_sizes) = bank_dropdown(detected.bank1_page_size, true);
+    grid.attach(&gtk::Label::new(Some("Bank 1")), 0, 2, 1, 1);
+    grid.attach(&bank1, 1, 2, 2, 1);
+
+    let rotation = gtk::DropDown::from_strings(&ROTATIONS);
+    grid.attach(&gtk::Label::new(Some("Rotation")), 0, 3, 1, 1);
+    grid.attach(&rotation, 1, 3, 2, 1);
+
+    let btn_save = gtk::Button::with_label("Save as .lnx");
+    grid.attach(&btn_save, 0, 4, 1, 1);
+
+    let btn_ok = gtk::Button::with_label("Load");
+    grid.attach(&btn_ok, 1, 4, 1, 1);
+
+    let btn_cancel = gtk::Button::with_label("Cancel");
+    grid.attach(&btn_cancel, 2, 4, 1, 1);
+
+    let win = ApplicationWindow::builder()
+        .modal(true)
+        .transient_for(parent)
+        .title("Headerless cartridge")
+        .child(&grid)
+        .build();
+
+    let header = Rc::new(clone!(
+        #[weak] bank0,
+        #[weak] bank1,
+        #[weak] rotation,
+        #[upgrade_or] detected.clone(),
+        move || LnxHeader {
+            bank0_page_size: bank0_sizes[bank0.selected() as usize],
+            bank1_page_size: bank1_sizes[bank1.selected() as usize],
+            rotation: rotation.selected() as u8,
+            title: stem.clone(),
+            ..detected.clone()
+        }
+    ));
+
+    btn_cancel.connect_clicked(clone!(
+        #[weak] win,
+        move |_| win.close()
+    ));
+
+    btn_ok.connect_clicked(clone!(
+        #[weak] win,
+        #[strong] header,
+        move |_| {
+            event_tx.send(Event::LoadHeaderlessCart(file.clone(), entry.clone(), header())).unwrap();
+            win.close();
+        }
+    ));
+
+    btn_save.connect_clicked(clone!(
+        #[weak] win,
+        move |_| {
+            let filters = gio::ListStore::new::<gtk::FileFilter>();
+            let lnx_filter = gtk::FileFilter::new();
+            lnx_filter.add_suffix("lnx");
+            lnx_filter.set_name(Some("lnx"));
+            filters.append(&lnx_filter);
+
+            let filedialog = gtk::FileDialog::builder()
+                .title("Save as .lnx")
+                .modal(true)
+                .filters(&filters)
+                .initial_name(format!("{}.lnx", header().title))
+                .build();
+
+            let image = header().lnx_image(&data);
+            filedialog.save(Some(&win), gio::Cancellable::NONE, move |file| {
+                if let Ok(file) = file {
+                    let filename = file.path().expect("Couldn't get file path");
+                    if let Err(e) = std::fs::write(&filename, image) {
+                        error!("Couldn't write '{}'. '{}'", filename.display(), e);
+                    }
+                }
+            });
+        }
+    ));
+
+    win.present();
+}
+
+fn show_cart_info(event_tx: kanal::Sender<Event>, parent: &ApplicationWindow, header: LnxHeader, writable: bool) {
+    let grid = gtk::Grid::builder()
+        .margin_start(6).margin_end(6).margin_top(6).margin_bottom(6)
+        .halign(gtk::Align::Start).valign(gtk::Align::Center)
+        .row_spacing(6).column_spacing(6)
+        .build();
+
+    let title = gtk::Entry::builder().text(&header.title).max_length(31).hexpand(true).build();
+    grid.attach(&gtk::Label::new(Some("Title")), 0, 0, 1, 1);
+    grid.attach(&title, 1, 0, 2, 1);
+
+    let manufacturer = gtk::Entry::builder().text(&header.manufacturer).max_length(15).hexpand(true).build();
+    grid.attach(&gtk::Label::new(Some("Manufacturer")), 0, 1, 1, 1);
+    grid.attach(&manufacturer, 1, 1, 2, 1);
+
+    let (bank0, bank0_sizes) = bank_dropdown(header.bank0_page_size, false);
+    grid.attach(&gtk::Label::new(Some("Bank 0")), 0, 2, 1, 1);
+    grid.attach(&bank0, 1, 2, 2, 1);
+
+    let (bank1, bank1_sizes) = bank_dropdown(header.bank1_page_size, true);
+    grid.attach(&gtk::Label::new(Some("Bank 1")), 0, 3, 1, 1);
+    grid.attach(&bank1, 1, 3, 2, 1);
+
+    let rotation = gtk::DropDown::from_strings(&ROTATIONS);
+    rotation.set_selected(header.rotation as u32);
+    grid.attach(&gtk::Label::new(Some("Rotation")), 0, 4, 1, 1);
+    grid.attach(&rotation, 1, 4, 2, 1);
+
+    let eeprom = gtk::DropDown::from_strings(&EEPROM_TYPES);
+    eeprom.set_selected(header.eeprom_type() as u32);
+    grid.attach(&gtk::Label::new(Some("EEPROM")), 0, 5, 1, 1);
+    grid.attach(&eeprom, 1, 5, 2, 1);
+
+    let audin = gtk::CheckButton::builder().label("AUDIN bank switching").active(header.audin).build();
+    grid.attach(&audin, 1, 6, 2, 1);
+
+    grid.attach(&gtk::Label::new(Some("Version")), 0, 7, 1, 1);
+    grid.attach(&gtk::Label::new(Some(&header.version.to_string())), 1, 7, 2, 1);
+
+    let btn_write = gtk::Button::with_label("Write");
+    btn_write.set_sensitive(writable);
+    if !writable {
+        btn_write.set_tooltip_text(Some("Only plain .lnx files can be written"));
+    }
+    grid.attach(&btn_write, 1, 8, 1, 1);
+
+    let btn_close = gtk::Button::with_label("Close");
+    grid.attach(&btn_close, 2, 8, 1, 1);
+
+    let win = ApplicationWindow::builder()
+        .modal(true)
+        .transient_for(parent)
+        .title("Cartridge info")
+        .child(&grid)
+        .build();
+
+    btn_close.connect_clicked(clone!(
+        #[weak] win,
+        move |_| win.close()
+    ));
+
+    btn_write.connect_clicked(clone!(
+        #[weak] win,
+        move |_| {
+            let mut edited = LnxHeader {
+                title: title.text().trim().to_string(),
+                manufacturer: manufacturer.text().trim().to_string(),
+                bank0_page_size: bank0_sizes[bank0.selected() as usize],
+                bank1_page_size: bank1_sizes[bank1.selected() as usize],
+                rotation: rotation.selected() as u8,
+                audin: audin.is_active(),
+                ..header.clone()
+            };
+            edited.set_eeprom_type(eeprom.selected() as usize);
+            event_tx.send(Event::WriteCartHeader(edited)).unwrap();
+            win.close();
+        }
+    ));
+
+    win.present();
+}
+
+/// Lets the user pick the cartridge to load from an archive holding several.
+fn show_archive_chooser(event_tx: kanal::Sender<Event>, parent: &ApplicationWindow, file: PathBuf, entries: Vec<String>) {
+    let list = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::Single)
+        .activate_on_single_click(false)
+        .build();
+    for entry in &entries {
+        list.append(&gtk::Label::builder()
+            .label(entry)
+            .halign(gtk::Align::Start)
+            .margin_start(6).margin_end(6).margin_top(3).margin_bottom(3)
+            .build());
+    }
+    list.select_row(list.row_at_index(0).as_ref());
+
+    let scrolled = gtk::ScrolledWindow::builder()
+        .child(&list)
+        .min_content_width(320)
+        .min_content_height(240)
+        .vexpand(true)
+        .build();
+
+    let btn_ok = gtk::Button::with_label("OK");
+    let btn_cancel = gtk::Button::with_label("Cancel");
+
+    let grid = gtk::Grid::builder()
+        .margin_start(6).margin_end(6).margin_top(6).margin_bottom(6)
+        .row_spacing(6).column_spacing(6)
+        .build();
+    grid.attach(&scrolled, 0, 0, 2, 1);
+    grid.attach(&btn_ok, 0, 1, 1, 1);
+    grid.attach(&btn_cancel, 1, 1, 1, 1);
+
+    let title = file.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let win = ApplicationWindow::builder()
+        .modal(true)
+        .transient_for(parent)
+        .title(&title)
+        .child(&grid)
+        .build();
+
+    let entries = Rc::new(entries);
+    let load = Rc::new(clone!(
+        #[weak] win,
+        move |index: i32| {
+            if let Some(entry) = usize::try_from(index).ok().and_then(|i| entries.get(i)) {
+                event_tx.send(Event::LoadCartEntry(file.clone(), entry.clone())).unwrap();
+            }
+            win.close();
+        }
+    ));
+
+    list.connect_row_activated(clone!(
+        #[strong] load,
+        move |_, row| load(row.index())
+    ));
+
+    btn_ok.connect_clicked(clone!(
+        #[weak] list,
+        move |_| load(list.selected_row().map_or(-1, |row| row.index()))
+    ));
+
+    btn_cancel.connect_clicked(clone!(
+        #[weak] win,
+        move |_| win.close()
+    ));
+
+    win.present();
+}
+
 fn show_state_picker(event_tx: kanal::Sender<Event>, window: &ApplicationWindow) {
     let filters = gio::ListStore::new::<gtk::FileFilter>();
 
@@ -555,6 +2416,36 @@ fn show_state_picker(event_tx: kanal::Sender<Event>, window: &ApplicationWindow)
     });
 }
 
+/// Picks the `.eep` file to import into the cart EEPROM, or to export it to.
+fn show_eeprom_picker(event_tx: kanal::Sender<Event>, window: &ApplicationWindow, export: bool) {
+    let filters = gio::ListStore::new::<gtk::FileFilter>();
+
+    let eep_filter = gtk::FileFilter::new();
+    eep_filter.add_suffix("eep");
+    eep_filter.set_name(Some("eep"));
+    filters.append(&eep_filter);
+
+    let filedialog = gtk::FileDialog::builder()
+        .title(if export { "Export EEPROM" } else { "Import EEPROM" })
+        .modal(true)
+        .filters(&filters)
+        .build();
+
+    let txc = event_tx.clone();
+    let picked = move |file: Result<gio::File, glib::Error>| {
+        if let Ok(file) = file {
+            let filename = file.path().expect("Couldn't get file path");
+            let event = if export { Event::ExportEeprom(filename) } else { Event::ImportEeprom(filename) };
+            txc.send(event).unwrap();
+        }
+    };
+    if export {
+        filedialog.save(Some(window), gio::Cancellable::NONE, picked);
+    } else {
+        filedialog.open(Some(window), gio::Cancellable::NONE, picked);
+    }
+}
+
 fn show_state_writer(event_tx: kanal::Sender<Event>, window: &ApplicationWindow) {
     let filters = gio::ListStore::new::<gtk::FileFilter>();
 
@@ -0,0 +1,68 @@
+use std::path::{Path, PathBuf};
+
+use log::error;
+use notify::{
+    event::{AccessKind, AccessMode, ModifyKind},
+    EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
+
+use crate::Event;
+
+/// Whether the file system event may mean the file content changed. Metadata only changes and
+/// our own reads are left out.
+fn is_write(kind: &EventKind) -> bool {
+    match kind {
+        EventKind::Create(_) => true,
+        EventKind::Modify(ModifyKind::Metadata(_)) => false,
+        EventKind::Modify(_) => true,
+        EventKind::Access(AccessKind::Close(AccessMode::Write)) => true,
+        _ => false,
+    }
+}
+
+/// Sends `Event::CartFileChanged` when the cartridge file is written or replaced.
+pub(crate) struct CartWatcher {
+    file: PathBuf,
+    _watcher: RecommendedWatcher,
+}
+
+impl CartWatcher {
+    /// Watches the folder holding the file rather than the file itself, linkers often write a
+    /// new file and rename it over the old one.
+    pub(crate) fn new(file: &Path, event_tx: kanal::Sender<Event>) -> Option<Self> {
+        let name = file.file_name()?.to_os_string();
+        let folder = match file.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+            Ok(event) => {
+                if is_write(&event.kind) && event.paths.iter().any(|path| path.file_name() == Some(&name)) {
+                    let _ = event_tx.send(Event::CartFileChanged);
+                }
+            }
+            Err(e) => error!("Cartridge watch error. '{}'", e),
+        });
+        let mut watcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                error!("Couldn't create the cartridge watcher. '{}'", e);
+                return None;
+            }
+        };
+        if let Err(e) = watcher.watch(&folder, RecursiveMode::NonRecursive) {
+            error!("Couldn't watch '{}'. '{}'", folder.display(), e);
+            return None;
+        }
+
+        Some(Self {
+            file: file.to_path_buf(),
+            _watcher: watcher,
+        })
+    }
+
+    pub(crate) fn file(&self) -> &Path {
+        &self.file
+    }
+}
@@ -0,0 +1,114 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+use super::{CartInfo, MAX_CART_SIZE};
+
+pub(crate) const ARCHIVE_EXTENSIONS: [&str; 3] = ["zip", "gz", "7z"];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Archive {
+    Zip,
+    Gzip,
+    SevenZip,
+}
+
+impl Archive {
+    fn of(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "zip" => Some(Archive::Zip),
+            "gz" => Some(Archive::Gzip),
+            "7z" => Some(Archive::SevenZip),
+            _ => None,
+        }
+    }
+}
+
+fn seven_zip_error(e: sevenz_rust::Error) -> io::Error {
+    io::Error::other(e.to_string())
+}
+
+/// Reads the whole content into `data`, failing once it grows past `MAX_CART_SIZE` so an
+/// archive can't decompress to more than a cartridge.
+fn read_limited(reader: impl Read, data: &mut Vec<u8>) -> io::Result<()> {
+    reader.take(MAX_CART_SIZE as u64 + 1).read_to_end(data)?;
+    match data.len() > MAX_CART_SIZE {
+        true => Err(io::Error::new(io::ErrorKind::InvalidData, "File too large for a cartridge")),
+        false => Ok(()),
+    }
+}
+
+/// Cartridge files in an archive, `None` when `path` isn't an archive.
+pub(crate) fn archive_entries(path: &Path) -> io::Result<Option<Vec<String>>> {
+    let Some(archive) = Archive::of(path) else {
+        return Ok(None);
+    };
+    let names: Vec<String> = match archive {
+        Archive::Zip => zip::ZipArchive::new(File::open(path)?)?
+            .file_names()
+            .map(|name| name.to_string())
+            .collect(),
+        // A gzip file holds a single file, named like the archive without its extension.
+        Archive::Gzip => path.file_stem().map(|stem| stem.to_string_lossy().to_string()).into_iter().collect(),
+        Archive::SevenZip => sevenz_rust::SevenZReader::open(path, sevenz_rust::Password::empty())
+            .map_err(seven_zip_error)?
+            .archive()
+            .files
+            .iter()
+            .filter(|entry| !entry.is_directory())
+            .map(|entry| entry.name().to_string())
+            .collect(),
+    };
+    let mut entries: Vec<String> = names
+        .into_iter()
+        .filter(|name| archive == Archive::Gzip || CartInfo::is_cart_file(Path::new(name)))
+        .collect();
+    entries.sort();
+    Ok(Some(entries))
+}
+
+/// Content of a cartridge file. For an archive `entry` names the file to extract, the first
+/// cartridge found is used when it is `None`.
+pub(crate) fn read_cart_file(path: &Path, entry: Option<&str>) -> io::Result<Vec<u8>> {
+    let mut data = vec![];
+    let Some(archive) = Archive::of(path) else {
+        read_limited(File::open(path)?, &mut data)?;
+        return Ok(data);
+    };
+    let entry = match entry {
+        Some(entry) => entry.to_string(),
+        None => archive_entries(path)?
+            .and_then(|entries| entries.into_iter().next())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No cartridge in archive"))?,
+    };
+
+    match archive {
+        Archive::Zip => {
+            read_limited(zip::ZipArchive::new(File::open(path)?)?.by_name(&entry)?, &mut data)?;
+        }
+        Archive::Gzip => {
+            read_limited(flate2::read::GzDecoder::new(File::open(path)?), &mut data)?;
+        }
+        Archive::SevenZip => {
+            let mut found = false;
+            sevenz_rust::SevenZReader::open(path, sevenz_rust::Password::empty())
+                .map_err(seven_zip_error)?
+                .for_each_entries(|archive_entry, reader| {
+                    if archive_entry.name() != entry {
+                        io::copy(reader, &mut io::sink())?;
+                        return Ok(true);
+                    }
+                    read_limited(reader, &mut data)?;
+                    found = true;
+                    Ok(false)
+                })
+                .map_err(seven_zip_error)?;
+            if !found {
+                return Err(io::Error::new(io::ErrorKind::NotFound, format!("'{}' not found in archive", entry)));
+            }
+        }
+    }
+    Ok(data)
+}
@@ -0,0 +1,87 @@
+use std::{io, path::Path};
+
+use super::{
+    archive::read_cart_file,
+    has_extension, is_headerless,
+    lnx_header::{LnxHeader, BANK_PAGE_SIZES, LNX_HEADER_SIZE, ROTATIONS},
+};
+
+/// Header fields set from the command line, the others are kept or detected.
+pub(crate) struct HeaderOverrides {
+    /// Bank sizes in KiB, 0 for no bank 1.
+    pub(crate) bank0: Option<u32>,
+    pub(crate) bank1: Option<u32>,
+    pub(crate) rotation: Option<String>,
+    pub(crate) title: Option<String>,
+    pub(crate) manufacturer: Option<String>,
+}
+
+fn page_size(kib: u32, allow_none: bool) -> io::Result<u16> {
+    if kib == 0 && allow_none {
+        return Ok(0);
+    }
+    BANK_PAGE_SIZES
+        .iter()
+        .copied()
+        .find(|page_size| *page_size as u32 / 4 == kib)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid bank size {}K, expected 64, 128, 256 or 512", kib)))
+}
+
+impl HeaderOverrides {
+    fn is_empty(&self) -> bool {
+        self.bank0.is_none()
+            && self.bank1.is_none()
+            && self.rotation.is_none()
+            && self.title.is_none()
+            && self.manufacturer.is_none()
+    }
+
+    fn apply(&self, header: &mut LnxHeader) -> io::Result<()> {
+        if let Some(bank0) = self.bank0 {
+            header.bank0_page_size = page_size(bank0, false)?;
+        }
+        if let Some(bank1) = self.bank1 {
+            header.bank1_page_size = page_size(bank1, true)?;
+        }
+        if let Some(rotation) = &self.rotation {
+            header.rotation = ROTATIONS
+                .iter()
+                .position(|r| r.eq_ignore_ascii_case(rotation))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid rotation '{}'", rotation)))? as u8;
+        }
+        if let Some(title) = &self.title {
+            header.title = title.clone();
+        }
+        if let Some(manufacturer) = &self.manufacturer {
+            header.manufacturer = manufacturer.clone();
+        }
+        Ok(())
+    }
+}
+
+/// Converts between `.lnx` images and headerless `.lyx`/`.bin` dumps, going by the output
+/// extension. Converting a `.lnx` to a `.lnx` rewrites its header with the overrides, they are
+/// refused for a headerless output.
+pub(crate) fn convert(input: &Path, output: &Path, overrides: &HeaderOverrides) -> io::Result<()> {
+    let data = read_cart_file(input, None)?;
+    let (mut header, content) = match LnxHeader::parse(&data) {
+        Some(header) => (header, &data[LNX_HEADER_SIZE..]),
+        None if has_extension(input, &["o"]) => {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Homebrew .o files aren't cartridge images"));
+        }
+        None => {
+            let mut header = LnxHeader::detect(data.len());
+            header.title = input.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+            (header, &data[..])
+        }
+    };
+
+    if is_headerless(output) {
+        if !overrides.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Header options need a .lnx output"));
+        }
+        return std::fs::write(output, content);
+    }
+    overrides.apply(&mut header)?;
+    std::fs::write(output, header.lnx_image(content))
+}
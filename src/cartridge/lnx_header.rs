@@ -0,0 +1,187 @@
+pub(crate) const LNX_HEADER_SIZE: usize = 64;
+const LNX_MAGIC: &[u8; 4] = b"LYNX";
+const TITLE_SIZE: usize = 32;
+const MANUFACTURER_SIZE: usize = 16;
+/// Pages in a bank, the page size fields give the bytes in each of them.
+const BANK_PAGES: u32 = 256;
+/// Page sizes of the 64K, 128K, 256K and 512K banks.
+pub(crate) const BANK_PAGE_SIZES: [u16; 4] = [256, 512, 1024, 2048];
+const LNX_VERSION: u16 = 1;
+/// Serial EEPROM chips, indexed by the 3 low bits of the EEPROM byte.
+pub(crate) const EEPROM_TYPES: [&str; 6] = ["None", "93C46", "93C56", "93C66", "93C76", "93C86"];
+const EEPROM_TYPE_MASK: u8 = 0x07;
+pub(crate) const ROTATIONS: [&str; 3] = ["None", "Left", "Right"];
+
+fn text(data: &[u8]) -> String {
+    let end = data.iter().position(|b| *b == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..end]).trim().to_string()
+}
+
+/// Smallest bank page size holding `size` bytes, the largest one when none does.
+fn fitting_page_size(size: usize) -> u16 {
+    BANK_PAGE_SIZES
+        .iter()
+        .copied()
+        .find(|page_size| *page_size as usize * BANK_PAGES as usize >= size)
+        .unwrap_or(BANK_PAGE_SIZES[BANK_PAGE_SIZES.len() - 1])
+}
+
+/// 64 bytes header in front of the `.lnx` cartridge images.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct LnxHeader {
+    pub(crate) bank0_page_size: u16,
+    pub(crate) bank1_page_size: u16,
+    pub(crate) version: u16,
+    pub(crate) title: String,
+    pub(crate) manufacturer: String,
+    /// 0 none, 1 left, 2 right.
+    pub(crate) rotation: u8,
+    pub(crate) audin: bool,
+    pub(crate) eeprom: u8,
+}
+
+impl LnxHeader {
+    pub(crate) fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < LNX_HEADER_SIZE || &data[0..4] != LNX_MAGIC {
+            return None;
+        }
+        let word = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+        Some(Self {
+            bank0_page_size: word(4),
+            bank1_page_size: word(6),
+            version: word(8),
+            title: text(&data[10..10 + TITLE_SIZE]),
+            manufacturer: text(&data[42..42 + MANUFACTURER_SIZE]),
+            rotation: data[58],
+            audin: data[59] != 0,
+            eeprom: data[60],
+        })
+    }
+
+    /// Header for a headerless dump of `size` bytes, filling bank 0 before bank 1.
+    pub(crate) fn detect(size: usize) -> Self {
+        let bank0_page_size = fitting_page_size(size);
+        let rest = size.saturating_sub(bank0_page_size as usize * BANK_PAGES as usize);
+        Self {
+            bank0_page_size,
+            bank1_page_size: if rest == 0 { 0 } else { fitting_page_size(rest) },
+            version: LNX_VERSION,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn to_bytes(&self) -> [u8; LNX_HEADER_SIZE] {
+        let mut bytes = [0; LNX_HEADER_SIZE];
+        let mut put_text = |offset: usize, size: usize, text: &str| {
+            // Keeps a terminating zero and doesn't split a character.
+            let end = text
+                .char_indices()
+                .map(|(i, c)| i + c.len_utf8())
+                .take_while(|end| *end < size)
+                .last()
+                .unwrap_or(0);
+            let text = &text.as_bytes()[..end];
+            bytes[offset..offset + text.len()].copy_from_slice(text);
+        };
+        put_text(10, TITLE_SIZE, &self.title);
+        put_text(42, MANUFACTURER_SIZE, &self.manufacturer);
+        bytes[0..4].copy_from_slice(LNX_MAGIC);
+        bytes[4..6].copy_from_slice(&self.bank0_page_size.to_le_bytes());
+        bytes[6..8].copy_from_slice(&self.bank1_page_size.to_le_bytes());
+        bytes[8..10].copy_from_slice(&self.version.to_le_bytes());
+        bytes[58] = self.rotation;
+        bytes[59] = self.audin as u8;
+        bytes[60] = self.eeprom;
+        bytes
+    }
+
+    /// `.lnx` image made of this header followed by the headerless cart data.
+    pub(crate) fn lnx_image(&self, data: &[u8]) -> Vec<u8> {
+        let mut image = self.to_bytes().to_vec();
+        image.extend_from_slice(data);
+        image
+    }
+
+    pub(crate) fn bank0_size(&self) -> u32 {
+        self.bank0_page_size as u32 * BANK_PAGES
+    }
+
+    pub(crate) fn bank1_size(&self) -> u32 {
+        self.bank1_page_size as u32 * BANK_PAGES
+    }
+
+    pub(crate) fn rotation_label(&self) -> &'static str {
+        ROTATIONS.get(self.rotation as usize).copied().unwrap_or(ROTATIONS[0])
+    }
+
+    /// Index in `EEPROM_TYPES`, 0 when the cart has no EEPROM.
+    pub(crate) fn eeprom_type(&self) -> usize {
+        (self.eeprom & EEPROM_TYPE_MASK) as usize
+    }
+
+    /// Changes the EEPROM chip, keeping the SD card and 8 bits organisation flags.
+    pub(crate) fn set_eeprom_type(&mut self, eeprom_type: usize) {
+        self.eeprom = (self.eeprom & !EEPROM_TYPE_MASK) | (eeprom_type as u8 & EEPROM_TYPE_MASK);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KIB: usize = 1024;
+
+    #[test]
+    fn round_trip() {
+        let header = LnxHeader {
+            bank0_page_size: 1024,
+            bank1_page_size: 512,
+            version: LNX_VERSION,
+            title: "Test title".to_string(),
+            manufacturer: "Maker".to_string(),
+            rotation: 2,
+            audin: true,
+            eeprom: 0x42,
+        };
+        assert_eq!(LnxHeader::parse(&header.to_bytes()), Some(header));
+    }
+
+    #[test]
+    fn parse_rejects_headerless() {
+        assert_eq!(LnxHeader::parse(&[0; LNX_HEADER_SIZE]), None);
+        assert_eq!(LnxHeader::parse(LNX_MAGIC), None);
+    }
+
+    #[test]
+    fn long_title_keeps_terminator() {
+        let header = LnxHeader {
+            title: "x".repeat(TITLE_SIZE + 8),
+            ..Default::default()
+        };
+        let bytes = header.to_bytes();
+        assert_eq!(bytes[10 + TITLE_SIZE - 1], 0);
+        assert_eq!(LnxHeader::parse(&bytes).unwrap().title, "x".repeat(TITLE_SIZE - 1));
+    }
+
+    #[test]
+    fn long_title_keeps_characters() {
+        let header = LnxHeader {
+            title: "é".repeat(TITLE_SIZE),
+            ..Default::default()
+        };
+        let title = LnxHeader::parse(&header.to_bytes()).unwrap().title;
+        assert_eq!(title, "é".repeat((TITLE_SIZE - 1) / 2));
+    }
+
+    #[test]
+    fn detect_bank_sizes() {
+        let banks = |size: usize| {
+            let header = LnxHeader::detect(size);
+            (header.bank0_page_size, header.bank1_page_size)
+        };
+        assert_eq!(banks(128 * KIB), (512, 0));
+        assert_eq!(banks(256 * KIB), (1024, 0));
+        assert_eq!(banks(512 * KIB), (2048, 0));
+        assert_eq!(banks(640 * KIB), (2048, 512));
+    }
+}
@@ -0,0 +1,106 @@
+pub(crate) mod archive;
+pub(crate) mod convert;
+pub(crate) mod lnx_header;
+pub(crate) mod patch;
+
+use std::path::{Path, PathBuf};
+use lnx_header::{LnxHeader, LNX_HEADER_SIZE};
+
+/// Homebrew executables start with `80 08`, the load address, the size and `BS93`.
+const BLL_HEADER_SIZE: usize = 10;
+const BLL_MAGIC: &[u8; 4] = b"BS93";
+
+/// Header and two full 512K banks, the largest image a cartridge can hold.
+pub(crate) const MAX_CART_SIZE: usize = LNX_HEADER_SIZE + 2 * 512 * 1024;
+
+pub(crate) const CART_EXTENSIONS: [&str; 2] = ["lnx", "o"];
+/// Raw cartridge dumps, without the LNX header.
+pub(crate) const HEADERLESS_EXTENSIONS: [&str; 2] = ["lyx", "bin"];
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| extensions.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Whether the file is a raw dump needing an LNX header to be loaded, going by its extension.
+pub(crate) fn is_headerless(path: &Path) -> bool {
+    has_extension(path, &HEADERLESS_EXTENSIONS)
+}
+
+/// Identifies a cartridge in the files kept per cart (play history, thumbnails, saves, cheats),
+/// computed over the ROM content as read, without its LNX header and before any patch. Editing
+/// the header or toggling patches keeps the files of the cart.
+pub(crate) fn cart_hash(data: &[u8]) -> u32 {
+    match LnxHeader::parse(data) {
+        Some(_) => crc32fast::hash(&data[LNX_HEADER_SIZE..]),
+        None => crc32fast::hash(data),
+    }
+}
+
+/// What the library shows about a cartridge file.
+#[derive(Clone, Debug)]
+pub(crate) struct CartInfo {
+    pub(crate) path: PathBuf,
+    pub(crate) hash: u32,
+    pub(crate) title: String,
+    pub(crate) manufacturer: String,
+    pub(crate) rotation: &'static str,
+    pub(crate) bank0_size: u32,
+    pub(crate) bank1_size: u32,
+}
+
+impl CartInfo {
+    pub(crate) fn read(path: &Path) -> Option<Self> {
+        let data = std::fs::read(path).ok()?;
+        let stem = path.file_stem()?.to_string_lossy().to_string();
+        let hash = cart_hash(&data);
+
+        if let Some(header) = LnxHeader::parse(&data) {
+            return Some(Self {
+                path: path.to_path_buf(),
+                hash,
+                title: match header.title.is_empty() {
+                    true => stem,
+                    false => header.title.clone(),
+                },
+                manufacturer: header.manufacturer.clone(),
+                rotation: header.rotation_label(),
+                bank0_size: header.bank0_size(),
+                bank1_size: header.bank1_size(),
+            });
+        }
+
+        if data.len() >= BLL_HEADER_SIZE && data[0..2] == [0x80, 0x08] && &data[6..10] == BLL_MAGIC {
+            return Some(Self {
+                path: path.to_path_buf(),
+                hash,
+                title: stem,
+                manufacturer: String::new(),
+                rotation: "None",
+                bank0_size: u16::from_be_bytes([data[4], data[5]]) as u32,
+                bank1_size: 0,
+            });
+        }
+
+        if is_headerless(path) {
+            let header = LnxHeader::detect(data.len());
+            return Some(Self {
+                path: path.to_path_buf(),
+                hash,
+                title: stem,
+                manufacturer: String::new(),
+                rotation: header.rotation_label(),
+                bank0_size: header.bank0_size(),
+                bank1_size: header.bank1_size(),
+            });
+        }
+
+        None
+    }
+
+    /// Whether the file is a cartridge image the library lists, going by its extension.
+    pub(crate) fn is_cart_file(path: &Path) -> bool {
+        has_extension(path, &CART_EXTENSIONS) || is_headerless(path)
+    }
+}
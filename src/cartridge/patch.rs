@@ -0,0 +1,398 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use super::MAX_CART_SIZE;
+
+pub(crate) const PATCH_EXTENSIONS: [&str; 3] = ["ips", "bps", "ups"];
+
+const IPS_MAGIC: &[u8] = b"PATCH";
+const IPS_EOF: u32 = 0x454f46;
+const BPS_MAGIC: &[u8] = b"BPS1";
+const UPS_MAGIC: &[u8] = b"UPS1";
+/// Source, target and patch CRC32 ending BPS and UPS files.
+const FOOTER_SIZE: usize = 12;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Rejects target sizes no cartridge can have before allocating them.
+fn check_target_size(target_size: usize) -> io::Result<()> {
+    match target_size > MAX_CART_SIZE {
+        true => Err(invalid("Patched cartridge too large")),
+        false => Ok(()),
+    }
+}
+
+/// Patch next to the cartridge file with the same name, `game.ips` for `game.lnx` or `game.zip`.
+pub(crate) fn find_patch(cartridge: &Path) -> Option<PathBuf> {
+    PATCH_EXTENSIONS
+        .iter()
+        .map(|extension| cartridge.with_extension(extension))
+        .find(|patch| patch.is_file())
+}
+
+/// Applies the IPS, BPS or UPS patch file to the cartridge data.
+pub(crate) fn apply_patch(data: &[u8], patch_file: &Path) -> io::Result<Vec<u8>> {
+    let patch = std::fs::read(patch_file)?;
+    if patch.starts_with(IPS_MAGIC) {
+        apply_ips(data, &patch)
+    } else if patch.starts_with(BPS_MAGIC) {
+        apply_bps(data, &patch)
+    } else if patch.starts_with(UPS_MAGIC) {
+        apply_ups(data, &patch)
+    } else {
+        Err(invalid("Unknown patch format"))
+    }
+}
+
+struct PatchReader<'a> {
+    patch: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PatchReader<'a> {
+    fn new(patch: &'a [u8], pos: usize) -> Self {
+        Self { patch, pos }
+    }
+
+    fn bytes(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let bytes = self.pos
+            .checked_add(len)
+            .and_then(|end| self.patch.get(self.pos..end))
+            .ok_or_else(|| invalid("Truncated patch"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> io::Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn be(&mut self, len: usize) -> io::Result<u32> {
+        Ok(self.bytes(len)?.iter().fold(0, |v, &b| (v << 8) | b as u32))
+    }
+
+    fn le32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    /// BPS and UPS variable length number.
+    fn number(&mut self) -> io::Result<usize> {
+        let overflow = || invalid("Invalid number in patch");
+        let mut data: usize = 0;
+        let mut shift: usize = 1;
+        let mut bits = 0;
+        loop {
+            let x = self.byte()?;
+            data = ((x & 0x7f) as usize)
+                .checked_mul(shift)
+                .and_then(|value| data.checked_add(value))
+                .ok_or_else(overflow)?;
+            if x & 0x80 != 0 {
+                return Ok(data);
+            }
+            bits += 7;
+            if bits > usize::BITS - 7 {
+                return Err(overflow());
+            }
+            shift <<= 7;
+            data = data.checked_add(shift).ok_or_else(overflow)?;
+        }
+    }
+
+    /// BPS signed relative offset.
+    fn offset(&mut self) -> io::Result<isize> {
+        let data = self.number()?;
+        let value = (data >> 1) as isize;
+        Ok(if data & 1 != 0 { -value } else { value })
+    }
+}
+
+fn apply_ips(data: &[u8], patch: &[u8]) -> io::Result<Vec<u8>> {
+    let mut target = data.to_vec();
+    let mut reader = PatchReader::new(patch, IPS_MAGIC.len());
+    loop {
+        let offset = reader.be(3)?;
+        if offset == IPS_EOF {
+            break;
+        }
+        let offset = offset as usize;
+        let (len, bytes) = match reader.be(2)? as usize {
+            0 => {
+                let len = reader.be(2)? as usize;
+                (len, vec![reader.byte()?; len])
+            }
+            len => (len, reader.bytes(len)?.to_vec()),
+        };
+        if target.len() < offset + len {
+            check_target_size(offset + len)?;
+            target.resize(offset + len, 0);
+        }
+        target[offset..offset + len].copy_from_slice(&bytes);
+    }
+    // Lunar IPS extension, the target size follows the end marker.
+    if let Ok(size) = reader.be(3) {
+        target.truncate(size as usize);
+    }
+    Ok(target)
+}
+
+/// Checks the patch and source CRCs, returns the expected target CRC.
+fn check_footer(data: &[u8], patch: &[u8]) -> io::Result<u32> {
+    if patch.len() < FOOTER_SIZE + 4 {
+        return Err(invalid("Truncated patch"));
+    }
+    let mut footer = PatchReader::new(patch, patch.len() - FOOTER_SIZE);
+    let (source_crc, target_crc, patch_crc) = (footer.le32()?, footer.le32()?, footer.le32()?);
+    if crc32fast::hash(&patch[..patch.len() - 4]) != patch_crc {
+        return Err(invalid("Corrupted patch, checksum mismatch"));
+    }
+    if crc32fast::hash(data) != source_crc {
+        return Err(invalid("The patch is meant for another version of the cartridge"));
+    }
+    Ok(target_crc)
+}
+
+fn apply_bps(data: &[u8], patch: &[u8]) -> io::Result<Vec<u8>> {
+    let target_crc = check_footer(data, patch)?;
+    let mut reader = PatchReader::new(patch, BPS_MAGIC.len());
+    let source_size = reader.number()?;
+    let target_size = reader.number()?;
+    let metadata_size = reader.number()?;
+    reader.bytes(metadata_size)?;
+    if source_size != data.len() {
+        return Err(invalid("The patch is meant for another version of the cartridge"));
+    }
+    check_target_size(target_size)?;
+
+    let mut target: Vec<u8> = Vec::with_capacity(target_size);
+    let mut source_offset: isize = 0;
+    let mut target_offset: isize = 0;
+    let out_of_bounds = || invalid("Patch copies out of bounds");
+    while reader.pos < patch.len() - FOOTER_SIZE {
+        let action = reader.number()?;
+        let len = (action >> 2) + 1;
+        if target.len() + len > target_size {
+            return Err(invalid("Patch writes past the target end"));
+        }
+        match action & 3 {
+            // Source read
+            0 => {
+                let pos = target.len();
+                target.extend_from_slice(data.get(pos..pos + len).ok_or_else(out_of_bounds)?);
+            }
+            // Target read
+            1 => target.extend_from_slice(reader.bytes(len)?),
+            // Source copy
+            2 => {
+                source_offset = source_offset.checked_add(reader.offset()?).ok_or_else(out_of_bounds)?;
+                let start = usize::try_from(source_offset).map_err(|_| out_of_bounds())?;
+                let end = start.checked_add(len).ok_or_else(out_of_bounds)?;
+                target.extend_from_slice(data.get(start..end).ok_or_else(out_of_bounds)?);
+                source_offset += len as isize;
+            }
+            // Target copy, byte by byte as the copied area can overlap the written one.
+            _ => {
+                target_offset = target_offset.checked_add(reader.offset()?).ok_or_else(out_of_bounds)?;
+                for _ in 0..len {
+                    let pos = usize::try_from(target_offset).map_err(|_| out_of_bounds())?;
+                    let byte = *target.get(pos).ok_or_else(out_of_bounds)?;
+                    target.push(byte);
+                    target_offset += 1;
+                }
+            }
+        }
+    }
+
+    if target.len() != target_size || crc32fast::hash(&target) != target_crc {
+        return Err(invalid("Patched cartridge checksum mismatch"));
+    }
+    Ok(target)
+}
+
+fn apply_ups(data: &[u8], patch: &[u8]) -> io::Result<Vec<u8>> {
+    let target_crc = check_footer(data, patch)?;
+    let mut reader = PatchReader::new(patch, UPS_MAGIC.len());
+    let source_size = reader.number()?;
+    let target_size = reader.number()?;
+    if source_size != data.len() {
+        return Err(invalid("The patch is meant for another version of the cartridge"));
+    }
+    check_target_size(target_size)?;
+
+    let mut target = data.to_vec();
+    target.resize(target_size, 0);
+    let mut pos: usize = 0;
+    while reader.pos < patch.len() - FOOTER_SIZE {
+        let skip = reader.number()?;
+        pos = pos.checked_add(skip).ok_or_else(|| invalid("Patch writes out of bounds"))?;
+        loop {
+            let x = reader.byte()?;
+            if let Some(byte) = target.get_mut(pos) {
+                *byte ^= x;
+            }
+            pos = pos.checked_add(1).ok_or_else(|| invalid("Patch writes out of bounds"))?;
+            if x == 0 {
+                break;
+            }
+        }
+    }
+
+    if crc32fast::hash(&target) != target_crc {
+        return Err(invalid("Patched cartridge checksum mismatch"));
+    }
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source() -> Vec<u8> {
+        (0..16).collect()
+    }
+
+    fn push_number(patch: &mut Vec<u8>, mut data: usize) {
+        loop {
+            let x = (data & 0x7f) as u8;
+            data >>= 7;
+            if data == 0 {
+                patch.push(0x80 | x);
+                return;
+            }
+            patch.push(x);
+            data -= 1;
+        }
+    }
+
+    fn push_footer(patch: &mut Vec<u8>, source: &[u8], target: &[u8]) {
+        patch.extend_from_slice(&crc32fast::hash(source).to_le_bytes());
+        patch.extend_from_slice(&crc32fast::hash(target).to_le_bytes());
+        patch.extend_from_slice(&crc32fast::hash(patch).to_le_bytes());
+    }
+
+    /// Source with bytes 4 and 5 replaced by `AB` and `xyz` appended.
+    fn patched() -> Vec<u8> {
+        let mut target = source();
+        target[4..6].copy_from_slice(b"AB");
+        target.extend_from_slice(b"xyz");
+        target
+    }
+
+    fn bps_patch(target_size: usize) -> Vec<u8> {
+        let mut patch = BPS_MAGIC.to_vec();
+        push_number(&mut patch, 16);
+        push_number(&mut patch, target_size);
+        push_number(&mut patch, 0);
+        // Source read of 4 bytes.
+        push_number(&mut patch, (4 - 1) << 2);
+        // Target read of `AB`.
+        push_number(&mut patch, ((2 - 1) << 2) | 1);
+        patch.extend_from_slice(b"AB");
+        // Source copy of 10 bytes from offset 6.
+        push_number(&mut patch, ((10 - 1) << 2) | 2);
+        push_number(&mut patch, 6 << 1);
+        // Target read of `xyz`.
+        push_number(&mut patch, ((3 - 1) << 2) | 1);
+        patch.extend_from_slice(b"xyz");
+        push_footer(&mut patch, &source(), &patched());
+        patch
+    }
+
+    #[test]
+    fn ips_records_and_rle() {
+        let mut patch = IPS_MAGIC.to_vec();
+        patch.extend_from_slice(&[0, 0, 4, 0, 2]);
+        patch.extend_from_slice(b"AB");
+        patch.extend_from_slice(&[0, 0, 16, 0, 0, 0, 3, b'z']);
+        patch.extend_from_slice(b"EOF");
+
+        let mut target = source();
+        target[4..6].copy_from_slice(b"AB");
+        target.extend_from_slice(b"zzz");
+        assert_eq!(apply_ips(&source(), &patch).unwrap(), target);
+    }
+
+    #[test]
+    fn ips_truncates_to_lunar_size() {
+        let mut patch = IPS_MAGIC.to_vec();
+        patch.extend_from_slice(b"EOF");
+        patch.extend_from_slice(&[0, 0, 8]);
+        assert_eq!(apply_ips(&source(), &patch).unwrap(), source()[..8]);
+    }
+
+    #[test]
+    fn ips_truncated_record() {
+        let mut patch = IPS_MAGIC.to_vec();
+        patch.extend_from_slice(&[0, 0, 4, 0, 2, b'A']);
+        assert!(apply_ips(&source(), &patch).is_err());
+    }
+
+    #[test]
+    fn ips_target_too_large() {
+        let mut patch = IPS_MAGIC.to_vec();
+        patch.extend_from_slice(&[0xff, 0xff, 0xff, 0, 1, b'A']);
+        patch.extend_from_slice(b"EOF");
+        assert!(apply_ips(&source(), &patch).is_err());
+    }
+
+    #[test]
+    fn overlong_number() {
+        let mut patch = vec![0; 16];
+        patch.push(0x80);
+        assert!(PatchReader::new(&patch, 0).number().is_err());
+    }
+
+    #[test]
+    fn bps_actions() {
+        assert_eq!(apply_bps(&source(), &bps_patch(19)).unwrap(), patched());
+    }
+
+    #[test]
+    fn bps_wrong_source() {
+        let mut other = source();
+        other[0] = 0xff;
+        assert!(apply_bps(&other, &bps_patch(19)).is_err());
+    }
+
+    #[test]
+    fn bps_corrupted() {
+        let mut patch = bps_patch(19);
+        patch[8] ^= 0xff;
+        assert!(apply_bps(&source(), &patch).is_err());
+    }
+
+    #[test]
+    fn bps_target_too_large() {
+        assert!(apply_bps(&source(), &bps_patch(usize::MAX >> 8)).is_err());
+    }
+
+    fn ups_patch(target_size: usize) -> Vec<u8> {
+        let source = source();
+        let target = patched();
+        let mut patch = UPS_MAGIC.to_vec();
+        push_number(&mut patch, 16);
+        push_number(&mut patch, target_size);
+        // Bytes 4 and 5, then the 3 appended ones after skipping 16 - 7 bytes.
+        push_number(&mut patch, 4);
+        patch.extend_from_slice(&[source[4] ^ b'A', source[5] ^ b'B', 0]);
+        push_number(&mut patch, 16 - 7);
+        patch.extend_from_slice(&target[16..]);
+        patch.push(0);
+        push_footer(&mut patch, &source, &target);
+        patch
+    }
+
+    #[test]
+    fn ups_xor() {
+        assert_eq!(apply_ups(&source(), &ups_patch(19)).unwrap(), patched());
+    }
+
+    #[test]
+    fn ups_target_too_large() {
+        assert!(apply_ups(&source(), &ups_patch(MAX_CART_SIZE + 1)).is_err());
+    }
+}
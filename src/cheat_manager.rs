@@ -0,0 +1,156 @@
+use std::{cell::RefCell, rc::Rc};
+
+use gtk::{glib::clone, prelude::*};
+use log::error;
+use crate::{cheats::Cheat, Event};
+
+pub(crate) struct CheatManager {
+    window: gtk::ApplicationWindow,
+    list: gtk::ListBox,
+    cheats: Rc<RefCell<Vec<Cheat>>>,
+    event_tx: kanal::Sender<Event>,
+}
+
+fn hex_entry(placeholder: &str, max_length: i32) -> gtk::Entry {
+    gtk::Entry::builder()
+        .placeholder_text(placeholder)
+        .max_length(max_length)
+        .width_chars(max_length + 1)
+        .build()
+}
+
+fn rebuild(list: &gtk::ListBox, cheats: &Rc<RefCell<Vec<Cheat>>>, event_tx: &kanal::Sender<Event>) {
+    list.remove_all();
+
+    for (i, cheat) in cheats.borrow().iter().enumerate() {
+        let enabled = gtk::CheckButton::builder()
+            .active(cheat.enabled)
+            .build();
+        let label = gtk::Label::builder()
+            .label(format!("{}  {}", cheat.code(), cheat.name))
+            .halign(gtk::Align::Start)
+            .hexpand(true)
+            .build();
+        let remove_btn = gtk::Button::with_label("Remove");
+
+        let row = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(6)
+            .build();
+        row.append(&enabled);
+        row.append(&label);
+        row.append(&remove_btn);
+        list.append(&row);
+
+        enabled.connect_toggled(clone!(
+            #[strong] cheats,
+            #[strong] event_tx,
+            move |btn| {
+                if let Some(cheat) = cheats.borrow_mut().get_mut(i) {
+                    cheat.enabled = btn.is_active();
+                }
+                event_tx.send(Event::UpdateCheats(cheats.borrow().clone())).unwrap();
+            }
+        ));
+
+        remove_btn.connect_clicked(clone!(
+            #[strong] cheats,
+            #[strong] event_tx,
+            move |_| {
+                if i < cheats.borrow().len() {
+                    cheats.borrow_mut().remove(i);
+                }
+                event_tx.send(Event::UpdateCheats(cheats.borrow().clone())).unwrap();
+            }
+        ));
+    }
+}
+
+impl CheatManager {
+    pub(crate) fn new(event_tx: kanal::Sender<Event>, cheats: Vec<Cheat>) -> Self {
+        let cheats = Rc::new(RefCell::new(cheats));
+
+        let list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .build();
+        let scrolled = gtk::ScrolledWindow::builder()
+            .child(&list)
+            .min_content_width(360)
+            .min_content_height(240)
+            .vexpand(true)
+            .build();
+
+        let address_entry = hex_entry("Address", 4);
+        let value_entry = hex_entry("Value", 2);
+        let compare_entry = hex_entry("Compare", 2);
+        let name_entry = gtk::Entry::builder()
+            .placeholder_text("Name")
+            .hexpand(true)
+            .build();
+        let add_btn = gtk::Button::with_label("Add");
+
+        let add_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(6)
+            .build();
+        add_box.append(&address_entry);
+        add_box.append(&value_entry);
+        add_box.append(&compare_entry);
+        add_box.append(&name_entry);
+        add_box.append(&add_btn);
+
+        let vbox = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .margin_start(6).margin_end(6).margin_top(6).margin_bottom(6)
+            .spacing(6)
+            .build();
+        vbox.append(&scrolled);
+        vbox.append(&add_box);
+
+        let window = gtk::ApplicationWindow::builder()
+            .title("Cheats")
+            .child(&vbox)
+            .build();
+
+        add_btn.connect_clicked(clone!(
+            #[strong] event_tx,
+            move |_| {
+                let hex = |entry: &gtk::Entry| u16::from_str_radix(entry.text().trim(), 16).ok();
+                let (Some(address), Some(value)) = (hex(&address_entry), hex(&value_entry)) else {
+                    error!("Invalid cheat address or value.");
+                    return;
+                };
+                let compare = match compare_entry.text().trim() {
+                    "" => None,
+                    _ => match hex(&compare_entry) {
+                        None => {
+                            error!("Invalid cheat compare value.");
+                            return;
+                        }
+                        Some(c) => Some(c as u8),
+                    },
+                };
+                let cheat = Cheat::new(name_entry.text().to_string(), address, value as u8, compare);
+                event_tx.send(Event::AddCheat(cheat)).unwrap();
+                for entry in [&address_entry, &value_entry, &compare_entry, &name_entry] {
+                    entry.set_text("");
+                }
+            }
+        ));
+
+        rebuild(&list, &cheats, &event_tx);
+
+        window.present();
+
+        Self { window, list, cheats, event_tx }
+    }
+
+    pub(crate) fn set_cheats(&self, cheats: Vec<Cheat>) {
+        self.cheats.replace(cheats);
+        rebuild(&self.list, &self.cheats, &self.event_tx);
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        !self.window.is_visible()
+    }
+}
@@ -0,0 +1,79 @@
+use std::{fmt, path::Path, str::FromStr};
+
+/// RAM poke applied after every frame, only when the current value matches `compare` if set.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Cheat {
+    pub(crate) name: String,
+    pub(crate) address: u16,
+    pub(crate) value: u8,
+    pub(crate) compare: Option<u8>,
+    pub(crate) enabled: bool,
+}
+
+impl Cheat {
+    pub(crate) fn new(name: String, address: u16, value: u8, compare: Option<u8>) -> Self {
+        Self { name, address, value, compare, enabled: true }
+    }
+
+    pub(crate) fn code(&self) -> String {
+        match self.compare {
+            None => format!("{:04X}:{:02X}", self.address, self.value),
+            Some(compare) => format!("{:04X}:{:02X}:{:02X}", self.address, self.value, compare),
+        }
+    }
+}
+
+/// One cheat per line: `+AAAA:VV[:CC] name`, `-` instead of `+` for a disabled cheat.
+impl fmt::Display for Cheat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = if self.enabled { '+' } else { '-' };
+        write!(f, "{}{} {}", state, self.code(), self.name)
+    }
+}
+
+impl FromStr for Cheat {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim();
+        let (enabled, line) = match line.chars().next() {
+            Some('+') => (true, &line[1..]),
+            Some('-') => (false, &line[1..]),
+            _ => (true, line),
+        };
+        let (code, name) = line.split_once(' ').unwrap_or((line, ""));
+        let fields: Vec<&str> = code.split(':').collect();
+        let hex = |s: &str| u16::from_str_radix(s, 16).map_err(|e| format!("'{}': {}", s, e));
+        let byte = |s: &str| u8::from_str_radix(s, 16).map_err(|e| format!("'{}': {}", s, e));
+
+        let (address, value, compare) = match fields.as_slice() {
+            [address, value] => (hex(address)?, byte(value)?, None),
+            [address, value, compare] => (hex(address)?, byte(value)?, Some(byte(compare)?)),
+            _ => return Err(format!("Invalid cheat code '{}'", code)),
+        };
+
+        Ok(Self {
+            name: name.trim().to_string(),
+            address,
+            value,
+            compare,
+            enabled,
+        })
+    }
+}
+
+pub(crate) fn load_cheats(file: &Path) -> Vec<Cheat> {
+    match std::fs::read_to_string(file) {
+        Err(_) => vec![],
+        Ok(content) => content
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.parse().ok())
+            .collect(),
+    }
+}
+
+pub(crate) fn save_cheats(file: &Path, cheats: &[Cheat]) -> std::io::Result<()> {
+    let content: String = cheats.iter().map(|cheat| format!("{}\n", cheat)).collect();
+    std::fs::write(file, content)
+}
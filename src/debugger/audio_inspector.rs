@@ -0,0 +1,180 @@
+use std::{cell::RefCell, rc::Rc};
+
+use gtk::{glib::clone, prelude::*};
+use crate::{
+    runner::audio_channels::{AUDIO_BASE, AUDIO_CHANNELS, AUDIO_CHANNEL_SIZE, ATTEN_A, MPAN, MSTEREO},
+    Event,
+};
+use super::{DebugSnapshot, DebugView};
+
+const SCOPE_WIDTH: i32 = 256;
+const SCOPE_HEIGHT: i32 = 64;
+
+const VOLCNTRL: u16 = 0;
+const FEEDBACK: u16 = 1;
+const OUTPUT: u16 = 2;
+const SHIFT: u16 = 3;
+const BACKUP: u16 = 4;
+const CONTROL: u16 = 5;
+const COUNTER: u16 = 6;
+const OTHER: u16 = 7;
+
+struct ChannelView {
+    registers: gtk::Label,
+    scope: gtk::DrawingArea,
+    samples: Rc<RefCell<Vec<i8>>>,
+}
+
+pub(crate) struct AudioInspector {
+    window: gtk::ApplicationWindow,
+    channels: Vec<ChannelView>,
+    mixer: gtk::Label,
+    _buttons: Rc<(Vec<gtk::CheckButton>, Vec<gtk::CheckButton>)>,
+}
+
+impl AudioInspector {
+    pub(crate) fn new(event_tx: kanal::Sender<Event>, muted: u8, solo: u8) -> Self {
+        let grid = gtk::Grid::builder()
+            .margin_start(6).margin_end(6).margin_top(6).margin_bottom(6)
+            .row_spacing(6).column_spacing(12)
+            .build();
+
+        let mut channels = vec![];
+        let mut mute_btns = vec![];
+        let mut solo_btns = vec![];
+
+        for channel in 0..AUDIO_CHANNELS {
+            let row = channel as i32;
+            let samples: Rc<RefCell<Vec<i8>>> = Rc::new(RefCell::new(vec![]));
+
+            let registers = gtk::Label::builder()
+                .halign(gtk::Align::Start)
+                .css_classes(["monospace"])
+                .build();
+
+            let scope = gtk::DrawingArea::builder()
+                .content_width(SCOPE_WIDTH)
+                .content_height(SCOPE_HEIGHT)
+                .build();
+
+            scope.set_draw_func(clone!(
+                #[strong] samples,
+                move |_, cr, width, height| {
+                    cr.set_source_rgb(0., 0., 0.);
+                    cr.rectangle(0., 0., width as f64, height as f64);
+                    let _ = cr.fill();
+
+                    let samples = samples.borrow();
+                    if samples.len() < 2 {
+                        return;
+                    }
+                    let mid = height as f64 / 2.;
+                    let step = width as f64 / (samples.len() - 1) as f64;
+                    cr.set_source_rgb(0.2, 1., 0.2);
+                    cr.set_line_width(1.);
+                    for (i, sample) in samples.iter().enumerate() {
+                        let y = mid - *sample as f64 * mid / 128.;
+                        match i {
+                            0 => cr.move_to(0., y),
+                            _ => cr.line_to(i as f64 * step, y),
+                        }
+                    }
+                    let _ = cr.stroke();
+                }
+            ));
+
+            let mute_btn = gtk::CheckButton::builder()
+                .label("Mute")
+                .active(muted & (1 << channel) != 0)
+                .build();
+            let solo_btn = gtk::CheckButton::builder()
+                .label("Solo")
+                .active(solo & (1 << channel) != 0)
+                .build();
+
+            grid.attach(&gtk::Label::new(Some(format!("Channel {}", channel).as_str())), 0, row, 1, 1);
+            grid.attach(&registers, 1, row, 1, 1);
+            grid.attach(&scope, 2, row, 1, 1);
+            grid.attach(&mute_btn, 3, row, 1, 1);
+            grid.attach(&solo_btn, 4, row, 1, 1);
+
+            mute_btns.push(mute_btn);
+            solo_btns.push(solo_btn);
+            channels.push(ChannelView { registers, scope, samples });
+        }
+
+        let mixer = gtk::Label::builder()
+            .halign(gtk::Align::Start)
+            .build();
+        grid.attach(&mixer, 0, AUDIO_CHANNELS as i32, 5, 1);
+
+        let refresh_btn = gtk::Button::with_label("Refresh");
+        grid.attach(&refresh_btn, 0, AUDIO_CHANNELS as i32 + 1, 1, 1);
+
+        let tx = event_tx.clone();
+        refresh_btn.connect_clicked(move |_| {
+            tx.send(Event::RequestSnapshot).unwrap();
+        });
+
+        let mask = |btns: &[gtk::CheckButton]| {
+            btns.iter()
+                .enumerate()
+                .filter(|(_, btn)| btn.is_active())
+                .fold(0u8, |mask, (channel, _)| mask | (1 << channel))
+        };
+        let buttons = Rc::new((mute_btns, solo_btns));
+        for btn in buttons.0.iter().chain(buttons.1.iter()) {
+            btn.connect_toggled(clone!(
+                #[strong] event_tx,
+                #[weak] buttons,
+                move |_| {
+                    event_tx.send(Event::AudioChannels(mask(&buttons.0[..]), mask(&buttons.1[..]))).unwrap();
+                }
+            ));
+        }
+
+        let window = gtk::ApplicationWindow::builder()
+            .title("Audio channels")
+            .child(&grid)
+            .build();
+
+        window.present();
+
+        Self { window, channels, mixer, _buttons: buttons }
+    }
+}
+
+impl DebugView for AudioInspector {
+    fn update(&mut self, snapshot: &DebugSnapshot) {
+        for (channel, view) in self.channels.iter().enumerate() {
+            let base = AUDIO_BASE + channel as u16 * AUDIO_CHANNEL_SIZE;
+            let reg = |offset: u16| snapshot.register(base + offset);
+            let shift = ((reg(OTHER) as u16 & 0xf0) << 4) | reg(SHIFT) as u16;
+
+            view.registers.set_text(&format!(
+                "Backup {:02X}  Counter {:02X}  Control {:02X}\nVolume {:4}  Output {:4}\nFeedback {:02X}  Shift {:03X}  Atten {:02X}",
+                reg(BACKUP),
+                reg(COUNTER),
+                reg(CONTROL),
+                reg(VOLCNTRL) as i8,
+                reg(OUTPUT) as i8,
+                reg(FEEDBACK),
+                shift,
+                snapshot.register(ATTEN_A + channel as u16),
+            ));
+
+            view.samples.replace(snapshot.audio_samples(channel).to_vec());
+            view.scope.queue_draw();
+        }
+
+        self.mixer.set_text(&format!(
+            "Stereo disable {:02X}  Pan {:02X}",
+            snapshot.register(MSTEREO),
+            snapshot.register(MPAN)
+        ));
+    }
+
+    fn is_closed(&self) -> bool {
+        !self.window.is_visible()
+    }
+}
@@ -0,0 +1,125 @@
+use gtk::{gdk, glib, prelude::*};
+use holani::lynx::Lynx;
+use crate::runner::audio_channels::{AUDIO_BASE, AUDIO_CHANNELS, MSTEREO};
+
+pub(crate) mod scb;
+pub(crate) mod audio_inspector;
+pub(crate) mod profiler_view;
+pub(crate) mod ram_search;
+pub(crate) mod suzy_inspector;
+pub(crate) mod symbols;
+pub(crate) mod video_viewer;
+
+pub(crate) const SUZY_BASE: u16 = 0xfc00;
+pub(crate) const SCBNEXT: u16 = 0xfc10;
+pub(crate) const SPRSYS: u16 = 0xfc92;
+pub(crate) const HOFF: u16 = 0xfc04;
+pub(crate) const VOFF: u16 = 0xfc06;
+pub(crate) const VIDBAS: u16 = 0xfc08;
+pub(crate) const COLLBAS: u16 = 0xfc0a;
+pub(crate) const DISPADR: u16 = 0xfd94;
+pub(crate) const GREEN0: u16 = 0xfda0;
+pub(crate) const BLUERED0: u16 = 0xfdb0;
+/// End of the Mikey register space.
+const MIKEY_END: u16 = 0xfdff;
+
+/// Registers read by the debug views. Mikey has registers changing state when read, like SERDAT
+/// or the interrupt flags, so they're never read through the CPU bus.
+const SNAPSHOT_REGISTERS: [(u16, u16); 4] = [
+    (HOFF, COLLBAS + 1),
+    (AUDIO_BASE, MSTEREO),
+    (DISPADR, DISPADR + 1),
+    (GREEN0, BLUERED0 + 0x0f),
+];
+
+/// State of the emulated Lynx captured by the runner for the debug views.
+pub(crate) struct DebugSnapshot {
+    ram: Vec<u8>,
+    registers: Vec<u8>,
+    sprite_chains: Vec<u16>,
+    audio_samples: [Vec<i8>; AUDIO_CHANNELS],
+}
+
+impl DebugSnapshot {
+    pub(crate) fn capture(lynx: &Lynx, sprite_chains: Vec<u16>, audio_samples: [Vec<i8>; AUDIO_CHANNELS]) -> Self {
+        Self {
+            ram: lynx.ram_data().to_vec(),
+            registers: Self::capture_registers(lynx),
+            sprite_chains,
+            audio_samples,
+        }
+    }
+
+    fn capture_registers(lynx: &Lynx) -> Vec<u8> {
+        let mut registers = vec![0; (MIKEY_END - SUZY_BASE) as usize + 1];
+        for (start, end) in SNAPSHOT_REGISTERS {
+            for addr in start..=end {
+                registers[(addr - SUZY_BASE) as usize] = lynx.peek_register(addr);
+            }
+        }
+        registers
+    }
+
+    pub(crate) fn ram(&self, addr: u16) -> u8 {
+        self.ram[addr as usize]
+    }
+
+    pub(crate) fn ram_word(&self, addr: u16) -> u16 {
+        u16::from_le_bytes([self.ram(addr), self.ram(addr.wrapping_add(1))])
+    }
+
+    pub(crate) fn ram_data(&self) -> &[u8] {
+        &self.ram
+    }
+
+    /// Suzy or Mikey register, only the ones in `SNAPSHOT_REGISTERS` are captured, the others
+    /// read as 0.
+    pub(crate) fn register(&self, addr: u16) -> u8 {
+        self.registers[(addr - SUZY_BASE) as usize]
+    }
+
+    pub(crate) fn register_word(&self, addr: u16) -> u16 {
+        u16::from_le_bytes([self.register(addr), self.register(addr + 1)])
+    }
+
+    /// SCB addresses the sprite engine was started with during the last frame.
+    pub(crate) fn sprite_chains(&self) -> &[u16] {
+        &self.sprite_chains
+    }
+
+    /// Output of each audio channel for every sample of the last frame.
+    pub(crate) fn audio_samples(&self, channel: usize) -> &[i8] {
+        &self.audio_samples[channel]
+    }
+
+    /// 12-bit Mikey palette entry as 0x0GBR.
+    pub(crate) fn palette_entry(&self, index: u8) -> u16 {
+        let green = self.register(GREEN0 + index as u16) & 0x0f;
+        let bluered = self.register(BLUERED0 + index as u16);
+        ((green as u16) << 8) | bluered as u16
+    }
+
+    pub(crate) fn palette_rgba(&self, index: u8) -> [u8; 4] {
+        let entry = self.palette_entry(index);
+        let red = (entry & 0x0f) as u8;
+        let blue = ((entry >> 4) & 0x0f) as u8;
+        let green = ((entry >> 8) & 0x0f) as u8;
+        [red * 17, green * 17, blue * 17, 0xff]
+    }
+}
+
+pub(crate) trait DebugView {
+    fn update(&mut self, snapshot: &DebugSnapshot);
+    fn is_closed(&self) -> bool;
+}
+
+pub(crate) fn rgba_texture(data: Vec<u8>, width: usize, height: usize) -> gdk::Texture {
+    gdk::MemoryTexture::new(
+        width as i32,
+        height as i32,
+        gdk::MemoryFormat::R8g8b8a8,
+        &glib::Bytes::from_owned(data),
+        width * 4,
+    )
+    .upcast()
+}
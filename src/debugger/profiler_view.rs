@@ -0,0 +1,255 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use gtk::{gio, glib::{self, clone}, prelude::*};
+use log::error;
+use crate::{runner::profiler::ProfileReport, Event};
+use super::symbols::Symbols;
+
+const MAX_ROWS: usize = 100;
+const GRAPH_HEIGHT: i32 = 160;
+const DEFAULT_FRAMES: f64 = 60.;
+
+// Stacked in the per frame graph, the last one is used for everything else.
+const GRAPH_COLORS: [(f64, f64, f64); 8] = [
+    (0.90, 0.10, 0.29), (0.24, 0.71, 0.29), (1.00, 0.88, 0.10), (0.26, 0.39, 0.85),
+    (0.96, 0.51, 0.19), (0.57, 0.12, 0.71), (0.27, 0.94, 0.94), (0.50, 0.50, 0.50),
+];
+
+#[derive(Default)]
+struct Graph {
+    /// Cycles of each routine bucket, per frame.
+    frames: Vec<Vec<u64>>,
+}
+
+struct Widgets {
+    table: gtk::Grid,
+    legend: gtk::Label,
+    graph_area: gtk::DrawingArea,
+    graph: Rc<RefCell<Graph>>,
+}
+
+pub(crate) struct ProfilerView {
+    window: gtk::ApplicationWindow,
+    status: gtk::Label,
+    report: Rc<RefCell<Option<ProfileReport>>>,
+    symbols: Rc<RefCell<Symbols>>,
+    widgets: Rc<Widgets>,
+}
+
+fn routine_name(symbols: &Symbols, pc: u16) -> String {
+    match symbols.routine(pc) {
+        Some((_, name)) if !symbols.is_empty() => name.to_string(),
+        _ => format!("${:04X}", pc),
+    }
+}
+
+fn render(report: &ProfileReport, symbols: &Symbols, widgets: &Widgets) {
+    let mut routines: HashMap<String, u64> = HashMap::new();
+    for (pc, cycles) in &report.hotspots {
+        *routines.entry(routine_name(symbols, *pc)).or_default() += cycles;
+    }
+    let mut routines: Vec<(String, u64)> = routines.into_iter().collect();
+    routines.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let total: u64 = routines.iter().map(|(_, c)| c).sum::<u64>().max(1);
+    let frames = report.frames.len().max(1) as u64;
+
+    while let Some(child) = widgets.table.first_child() {
+        widgets.table.remove(&child);
+    }
+    for (col, title) in ["Routine", "Cycles", "%", "Per frame"].iter().enumerate() {
+        let label = gtk::Label::builder().label(*title).halign(gtk::Align::Start).build();
+        widgets.table.attach(&label, col as i32, 0, 1, 1);
+    }
+    for (row, (name, cycles)) in routines.iter().take(MAX_ROWS).enumerate() {
+        let values = [
+            name.clone(),
+            cycles.to_string(),
+            format!("{:.2}", *cycles as f64 * 100. / total as f64),
+            (cycles / frames).to_string(),
+        ];
+        for (col, value) in values.iter().enumerate() {
+            let label = gtk::Label::builder()
+                .label(value)
+                .halign(if col == 0 { gtk::Align::Start } else { gtk::Align::End })
+                .selectable(true)
+                .build();
+            widgets.table.attach(&label, col as i32, row as i32 + 1, 1, 1);
+        }
+    }
+
+    let buckets = GRAPH_COLORS.len() - 1;
+    let top: Vec<&String> = routines.iter().take(buckets).map(|(name, _)| name).collect();
+    let graph_frames = report
+        .frames
+        .iter()
+        .map(|frame| {
+            let mut values = vec![0u64; GRAPH_COLORS.len()];
+            for (pc, cycles) in frame {
+                let name = routine_name(symbols, *pc);
+                let bucket = top.iter().position(|t| **t == name).unwrap_or(buckets);
+                values[bucket] += cycles;
+            }
+            values
+        })
+        .collect();
+    widgets.graph.replace(Graph { frames: graph_frames });
+    widgets.graph_area.queue_draw();
+
+    let legend = top
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (GRAPH_COLORS[i], glib::markup_escape_text(name).to_string()))
+        .chain(std::iter::once((GRAPH_COLORS[buckets], "other".to_string())))
+        .map(|((r, g, b), name)| {
+            format!(
+                "<span foreground=\"#{:02x}{:02x}{:02x}\">■</span> {}",
+                (r * 255.) as u8, (g * 255.) as u8, (b * 255.) as u8, name
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("   ");
+    widgets.legend.set_markup(&legend);
+}
+
+impl ProfilerView {
+    pub(crate) fn new(event_tx: kanal::Sender<Event>) -> Self {
+        let report: Rc<RefCell<Option<ProfileReport>>> = Rc::new(RefCell::new(None));
+        let symbols: Rc<RefCell<Symbols>> = Rc::new(RefCell::new(Symbols::default()));
+
+        let frames_spin = gtk::SpinButton::with_range(1., 3600., 1.);
+        frames_spin.set_value(DEFAULT_FRAMES);
+        let start_btn = gtk::Button::with_label("Start");
+        let symbols_btn = gtk::Button::with_label("Load symbols");
+        let status = gtk::Label::new(Some("Idle"));
+
+        let controls = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(6)
+            .build();
+        controls.append(&gtk::Label::new(Some("Frames")));
+        controls.append(&frames_spin);
+        controls.append(&start_btn);
+        controls.append(&symbols_btn);
+        controls.append(&status);
+
+        let graph: Rc<RefCell<Graph>> = Rc::new(RefCell::new(Graph::default()));
+        let graph_area = gtk::DrawingArea::builder()
+            .content_height(GRAPH_HEIGHT)
+            .hexpand(true)
+            .build();
+
+        graph_area.set_draw_func(clone!(
+            #[strong] graph,
+            move |_, cr, width, height| {
+                cr.set_source_rgb(0., 0., 0.);
+                cr.rectangle(0., 0., width as f64, height as f64);
+                let _ = cr.fill();
+
+                let graph = graph.borrow();
+                let max = graph.frames.iter().map(|f| f.iter().sum::<u64>()).max().unwrap_or(0);
+                if max == 0 {
+                    return;
+                }
+                let bar_width = width as f64 / graph.frames.len() as f64;
+                for (i, frame) in graph.frames.iter().enumerate() {
+                    let mut y = height as f64;
+                    for (bucket, cycles) in frame.iter().enumerate() {
+                        let h = *cycles as f64 * height as f64 / max as f64;
+                        let (r, g, b) = GRAPH_COLORS[bucket];
+                        cr.set_source_rgb(r, g, b);
+                        cr.rectangle(i as f64 * bar_width, y - h, bar_width.max(1.), h);
+                        let _ = cr.fill();
+                        y -= h;
+                    }
+                }
+            }
+        ));
+
+        let legend = gtk::Label::builder()
+            .halign(gtk::Align::Start)
+            .wrap(true)
+            .build();
+
+        let table = gtk::Grid::builder()
+            .row_spacing(2).column_spacing(12)
+            .build();
+        let scrolled = gtk::ScrolledWindow::builder()
+            .child(&table)
+            .min_content_height(300)
+            .vexpand(true)
+            .build();
+
+        let vbox = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .margin_start(6).margin_end(6).margin_top(6).margin_bottom(6)
+            .spacing(6)
+            .build();
+        vbox.append(&controls);
+        vbox.append(&graph_area);
+        vbox.append(&legend);
+        vbox.append(&scrolled);
+
+        let window = gtk::ApplicationWindow::builder()
+            .title("Profiler")
+            .default_width(640)
+            .child(&vbox)
+            .build();
+
+        let widgets = Rc::new(Widgets { table, legend, graph_area, graph });
+
+        start_btn.connect_clicked(clone!(
+            #[weak] status,
+            move |_| {
+                status.set_text("Capturing...");
+                event_tx.send(Event::Profile(frames_spin.value_as_int() as u32)).unwrap();
+            }
+        ));
+
+        symbols_btn.connect_clicked(clone!(
+            #[weak] window,
+            #[strong] report,
+            #[strong] symbols,
+            #[strong] widgets,
+            move |_| {
+                let filedialog = gtk::FileDialog::builder()
+                    .title("Load symbols")
+                    .modal(true)
+                    .build();
+                filedialog.open(Some(&window), gio::Cancellable::NONE, clone!(
+                    #[strong] report,
+                    #[strong] symbols,
+                    #[strong] widgets,
+                    move |file| {
+                        let Ok(file) = file else {
+                            return;
+                        };
+                        match Symbols::load(&file.path().expect("Couldn't get file path")) {
+                            Err(e) => error!("Couldn't load symbols. '{}'", e),
+                            Ok(loaded) => {
+                                symbols.replace(loaded);
+                                if let Some(report) = report.borrow().as_ref() {
+                                    render(report, &symbols.borrow(), &widgets);
+                                }
+                            }
+                        }
+                    }
+                ));
+            }
+        ));
+
+        window.present();
+
+        Self { window, status, report, symbols, widgets }
+    }
+
+    pub(crate) fn update(&mut self, report: ProfileReport) {
+        self.status.set_text(&format!("{} frames captured", report.frames.len()));
+        render(&report, &self.symbols.borrow(), &self.widgets);
+        self.report.replace(Some(report));
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        !self.window.is_visible()
+    }
+}
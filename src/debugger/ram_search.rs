@@ -0,0 +1,177 @@
+use std::{cell::RefCell, rc::Rc};
+
+use gtk::{glib::clone, prelude::*};
+use log::error;
+use crate::{cheats::Cheat, Event};
+use super::{DebugSnapshot, DebugView, SUZY_BASE};
+
+const MAX_RESULTS: usize = 200;
+
+const COMPARISONS: [&str; 5] = [
+    "Equal to",
+    "Greater than",
+    "Less than",
+    "Changed since last search",
+    "Unchanged since last search",
+];
+
+enum Pending {
+    Start,
+    Search(usize, Option<u8>),
+}
+
+#[derive(Default)]
+struct SearchState {
+    candidates: Vec<u16>,
+    previous: Vec<u8>,
+    pending: Option<Pending>,
+}
+
+pub(crate) struct RamSearch {
+    window: gtk::ApplicationWindow,
+    list: gtk::ListBox,
+    count: gtk::Label,
+    state: Rc<RefCell<SearchState>>,
+}
+
+impl RamSearch {
+    pub(crate) fn new(event_tx: kanal::Sender<Event>) -> Self {
+        let state: Rc<RefCell<SearchState>> = Rc::new(RefCell::new(SearchState::default()));
+
+        let comparison = gtk::DropDown::from_strings(&COMPARISONS);
+        let value_entry = gtk::Entry::builder()
+            .placeholder_text("Value")
+            .max_length(2)
+            .width_chars(3)
+            .build();
+        let new_btn = gtk::Button::with_label("New search");
+        let search_btn = gtk::Button::with_label("Search");
+        let cheat_btn = gtk::Button::with_label("Add cheat");
+        let count = gtk::Label::new(Some("No search"));
+
+        let controls = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(6)
+            .build();
+        controls.append(&comparison);
+        controls.append(&value_entry);
+        controls.append(&new_btn);
+        controls.append(&search_btn);
+        controls.append(&cheat_btn);
+
+        let list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::Single)
+            .build();
+        let scrolled = gtk::ScrolledWindow::builder()
+            .child(&list)
+            .min_content_height(300)
+            .vexpand(true)
+            .build();
+
+        let vbox = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .margin_start(6).margin_end(6).margin_top(6).margin_bottom(6)
+            .spacing(6)
+            .build();
+        vbox.append(&controls);
+        vbox.append(&count);
+        vbox.append(&scrolled);
+
+        let window = gtk::ApplicationWindow::builder()
+            .title("RAM search")
+            .child(&vbox)
+            .build();
+
+        let value = |entry: &gtk::Entry| u8::from_str_radix(entry.text().trim(), 16).ok();
+
+        new_btn.connect_clicked(clone!(
+            #[strong] state,
+            #[strong] event_tx,
+            move |_| {
+                state.borrow_mut().pending = Some(Pending::Start);
+                event_tx.send(Event::RequestSnapshot).unwrap();
+            }
+        ));
+
+        search_btn.connect_clicked(clone!(
+            #[strong] state,
+            #[strong] event_tx,
+            #[weak] value_entry,
+            move |_| {
+                let index = comparison.selected() as usize;
+                let value = value(&value_entry);
+                if index < 3 && value.is_none() {
+                    error!("A hexadecimal value is needed for this search.");
+                    return;
+                }
+                state.borrow_mut().pending = Some(Pending::Search(index, value));
+                event_tx.send(Event::RequestSnapshot).unwrap();
+            }
+        ));
+
+        cheat_btn.connect_clicked(clone!(
+            #[strong] state,
+            #[weak] list,
+            move |_| {
+                let Some(row) = list.selected_row() else {
+                    return;
+                };
+                let state = state.borrow();
+                let Some(address) = state.candidates.get(row.index() as usize) else {
+                    return;
+                };
+                let value = value(&value_entry).unwrap_or(state.previous[*address as usize]);
+                let cheat = Cheat::new(format!("RAM ${:04X}", address), *address, value, None);
+                event_tx.send(Event::AddCheat(cheat)).unwrap();
+            }
+        ));
+
+        window.present();
+
+        Self { window, list, count, state }
+    }
+}
+
+impl DebugView for RamSearch {
+    fn update(&mut self, snapshot: &DebugSnapshot) {
+        let mut state = self.state.borrow_mut();
+        let Some(pending) = state.pending.take() else {
+            return;
+        };
+        let ram = &snapshot.ram_data()[..SUZY_BASE as usize];
+
+        match pending {
+            Pending::Start => state.candidates = (0..SUZY_BASE).collect(),
+            Pending::Search(index, value) => {
+                let previous = std::mem::take(&mut state.previous);
+                state.candidates.retain(|addr| {
+                    let current = ram[*addr as usize];
+                    let last = previous.get(*addr as usize).copied().unwrap_or(current);
+                    match (index, value) {
+                        (0, Some(v)) => current == v,
+                        (1, Some(v)) => current > v,
+                        (2, Some(v)) => current < v,
+                        (3, _) => current != last,
+                        (4, _) => current == last,
+                        _ => false,
+                    }
+                });
+            }
+        }
+        state.previous = ram.to_vec();
+
+        self.count.set_text(&format!("{} candidates", state.candidates.len()));
+        self.list.remove_all();
+        for addr in state.candidates.iter().take(MAX_RESULTS) {
+            let label = gtk::Label::builder()
+                .label(format!("${:04X}  {:02X}", addr, ram[*addr as usize]))
+                .halign(gtk::Align::Start)
+                .build();
+            self.list.append(&label);
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        !self.window.is_visible()
+    }
+}
@@ -0,0 +1,292 @@
+use super::DebugSnapshot;
+
+const MAX_CHAIN_LENGTH: usize = 512;
+const MAX_SPRITE_LINES: usize = 1024;
+
+const SPRITE_TYPES: [&str; 8] = [
+    "background shadow",
+    "background",
+    "boundary shadow",
+    "boundary",
+    "normal",
+    "non collidable",
+    "xor shadow",
+    "shadow",
+];
+
+/// Sprite control block as read from RAM, size and palette carried over from the previous SCBs when not reloaded.
+#[derive(Clone)]
+pub(crate) struct Scb {
+    pub(crate) address: u16,
+    pub(crate) sprctl0: u8,
+    pub(crate) sprctl1: u8,
+    pub(crate) sprcoll: u8,
+    pub(crate) next: u16,
+    pub(crate) data: u16,
+    pub(crate) hpos: i16,
+    pub(crate) vpos: i16,
+    pub(crate) hsize: u16,
+    pub(crate) vsize: u16,
+    pub(crate) palette: [u8; 16],
+}
+
+impl Scb {
+    pub(crate) fn bpp(&self) -> u32 {
+        ((self.sprctl0 >> 6) + 1) as u32
+    }
+
+    pub(crate) fn hflip(&self) -> bool {
+        self.sprctl0 & 0x20 != 0
+    }
+
+    pub(crate) fn vflip(&self) -> bool {
+        self.sprctl0 & 0x10 != 0
+    }
+
+    pub(crate) fn sprite_type(&self) -> &'static str {
+        SPRITE_TYPES[(self.sprctl0 & 0x07) as usize]
+    }
+
+    pub(crate) fn literal(&self) -> bool {
+        self.sprctl1 & 0x80 != 0
+    }
+
+    pub(crate) fn reuse_palette(&self) -> bool {
+        self.sprctl1 & 0x08 != 0
+    }
+
+    pub(crate) fn skip(&self) -> bool {
+        self.sprctl1 & 0x04 != 0
+    }
+
+    pub(crate) fn start_quadrant(&self) -> u8 {
+        self.sprctl1 & 0x03
+    }
+
+    pub(crate) fn collision_number(&self) -> u8 {
+        self.sprcoll & 0x0f
+    }
+
+    pub(crate) fn no_collide(&self) -> bool {
+        self.sprcoll & 0x20 != 0
+    }
+
+    fn background(&self) -> bool {
+        self.sprctl0 & 0x06 == 0
+    }
+
+    pub(crate) fn flags(&self) -> String {
+        [
+            (self.hflip(), "hflip"),
+            (self.vflip(), "vflip"),
+            (self.literal(), "literal"),
+            (self.reuse_palette(), "reuse palette"),
+            (self.skip(), "skip"),
+            (self.no_collide(), "no collide"),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, name)| *name)
+        .collect::<Vec<&str>>()
+        .join(", ")
+    }
+}
+
+/// Follows the SCBNEXT links starting at `start` until a null high byte is found.
+pub(crate) fn walk_chain(snapshot: &DebugSnapshot, start: u16) -> Vec<Scb> {
+    let mut scbs: Vec<Scb> = vec![];
+    let mut address = start;
+    let mut hsize = 0x100;
+    let mut vsize = 0x100;
+    let mut palette: [u8; 16] = core::array::from_fn(|i| i as u8);
+
+    while address & 0xff00 != 0 && scbs.len() < MAX_CHAIN_LENGTH && !scbs.iter().any(|s| s.address == address) {
+        let sprctl0 = snapshot.ram(address);
+        let sprctl1 = snapshot.ram(address.wrapping_add(1));
+        let mut offset = 11u16;
+
+        let reload = (sprctl1 >> 4) & 0x03;
+        if reload >= 1 {
+            hsize = snapshot.ram_word(address.wrapping_add(offset));
+            vsize = snapshot.ram_word(address.wrapping_add(offset + 2));
+            offset += 4;
+        }
+        if reload >= 2 {
+            offset += 2;
+        }
+        if reload == 3 {
+            offset += 2;
+        }
+        if sprctl1 & 0x08 == 0 {
+            for i in 0..8 {
+                let pens = snapshot.ram(address.wrapping_add(offset + i));
+                palette[i as usize * 2] = pens >> 4;
+                palette[i as usize * 2 + 1] = pens & 0x0f;
+            }
+        }
+
+        let scb = Scb {
+            address,
+            sprctl0,
+            sprctl1,
+            sprcoll: snapshot.ram(address.wrapping_add(2)),
+            next: snapshot.ram_word(address.wrapping_add(3)),
+            data: snapshot.ram_word(address.wrapping_add(5)),
+            hpos: snapshot.ram_word(address.wrapping_add(7)) as i16,
+            vpos: snapshot.ram_word(address.wrapping_add(9)) as i16,
+            hsize,
+            vsize,
+            palette,
+        };
+        address = scb.next;
+        scbs.push(scb);
+    }
+
+    scbs
+}
+
+struct BitReader<'a> {
+    snapshot: &'a DebugSnapshot,
+    address: u32,
+    end: u32,
+    bits: u32,
+    bit_count: u32,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, n: u32) -> Option<u8> {
+        while self.bit_count < n {
+            if self.address >= self.end {
+                return None;
+            }
+            self.bits = (self.bits << 8) | self.snapshot.ram(self.address as u16) as u32;
+            self.address += 1;
+            self.bit_count += 8;
+        }
+        self.bit_count -= n;
+        Some(((self.bits >> self.bit_count) & ((1 << n) - 1)) as u8)
+    }
+}
+
+fn decode_line(snapshot: &DebugSnapshot, scb: &Scb, start: u32, end: u32) -> Vec<u8> {
+    let bpp = scb.bpp();
+    let mut reader = BitReader { snapshot, address: start, end, bits: 0, bit_count: 0 };
+    let mut pens = vec![];
+
+    if scb.literal() {
+        let count = (end - start) * 8 / bpp;
+        for _ in 0..count {
+            match reader.read(bpp) {
+                Some(pen) => pens.push(pen),
+                None => break,
+            }
+        }
+        return pens;
+    }
+
+    while let (Some(literal), Some(count)) = (reader.read(1), reader.read(4)) {
+        if literal == 1 {
+            for _ in 0..=count {
+                match reader.read(bpp) {
+                    Some(pen) => pens.push(pen),
+                    None => return pens,
+                }
+            }
+        } else if count == 0 {
+            break;
+        } else {
+            match reader.read(bpp) {
+                Some(pen) => pens.extend(std::iter::repeat_n(pen, count as usize + 1)),
+                None => break,
+            }
+        }
+    }
+
+    pens
+}
+
+/// Pixels of an unscaled sprite relative to its hotspot.
+pub(crate) struct SpriteImage {
+    pub(crate) pixels: Vec<(i32, i32, u8)>,
+    pub(crate) min_x: i32,
+    pub(crate) min_y: i32,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+}
+
+pub(crate) fn decode(snapshot: &DebugSnapshot, scb: &Scb) -> SpriteImage {
+    let mut pixels = vec![];
+    let mut address = scb.data as u32;
+    let mut quadrant = scb.start_quadrant();
+    let mut quadrants = 0;
+    let mut line = 0i32;
+
+    for _ in 0..MAX_SPRITE_LINES {
+        let offset = snapshot.ram(address as u16) as u32;
+        if offset == 0 {
+            break;
+        }
+        if offset == 1 {
+            address += 1;
+            quadrant = (quadrant + 1) % 4;
+            quadrants += 1;
+            line = 0;
+            if quadrants == 4 {
+                break;
+            }
+            continue;
+        }
+
+        let left = (quadrant == 2 || quadrant == 3) != scb.hflip();
+        let up = (quadrant == 1 || quadrant == 2) != scb.vflip();
+        let y = if up { -1 - line } else { line };
+
+        for (i, pen) in decode_line(snapshot, scb, address + 1, address + offset).into_iter().enumerate() {
+            let x = if left { -1 - i as i32 } else { i as i32 };
+            pixels.push((x, y, pen));
+        }
+
+        line += 1;
+        address += offset;
+    }
+
+    let min_x = pixels.iter().map(|p| p.0).min().unwrap_or(0);
+    let max_x = pixels.iter().map(|p| p.0).max().unwrap_or(-1);
+    let min_y = pixels.iter().map(|p| p.1).min().unwrap_or(0);
+    let max_y = pixels.iter().map(|p| p.1).max().unwrap_or(-1);
+
+    SpriteImage {
+        pixels,
+        min_x,
+        min_y,
+        width: (max_x - min_x + 1) as usize,
+        height: (max_y - min_y + 1) as usize,
+    }
+}
+
+impl SpriteImage {
+    pub(crate) fn to_rgba(&self, snapshot: &DebugSnapshot, scb: &Scb) -> Vec<u8> {
+        let mut data = vec![0u8; self.width * self.height * 4];
+        for (x, y, pen) in &self.pixels {
+            if *pen == 0 && !scb.background() {
+                continue;
+            }
+            let offset = ((y - self.min_y) as usize * self.width + (x - self.min_x) as usize) * 4;
+            let color = snapshot.palette_rgba(scb.palette[*pen as usize]);
+            data[offset..offset + 4].copy_from_slice(&color);
+        }
+        data
+    }
+
+    /// Area covered on the Lynx screen once scaled and offset, as (x, y, width, height).
+    pub(crate) fn screen_rect(&self, scb: &Scb, hoff: i16, voff: i16) -> (f32, f32, f32, f32) {
+        let hscale = scb.hsize as f32 / 256.;
+        let vscale = scb.vsize as f32 / 256.;
+        (
+            (scb.hpos.wrapping_sub(hoff)) as f32 + self.min_x as f32 * hscale,
+            (scb.vpos.wrapping_sub(voff)) as f32 + self.min_y as f32 * vscale,
+            self.width as f32 * hscale,
+            self.height as f32 * vscale,
+        )
+    }
+}
@@ -0,0 +1,164 @@
+use std::{cell::RefCell, rc::Rc};
+
+use gtk::{gdk, glib::clone, prelude::*};
+use crate::{lynx_display::LynxDisplay, Event};
+use super::{rgba_texture, scb::{decode, walk_chain}, DebugSnapshot, DebugView, HOFF, VOFF};
+
+struct SpriteEntry {
+    label: String,
+    details: String,
+    texture: Option<gdk::Texture>,
+    rect: (f32, f32, f32, f32),
+}
+
+pub(crate) struct SuzyInspector {
+    window: gtk::ApplicationWindow,
+    list: gtk::ListBox,
+    entries: Rc<RefCell<Vec<SpriteEntry>>>,
+}
+
+impl SuzyInspector {
+    pub(crate) fn new(event_tx: kanal::Sender<Event>, display: &LynxDisplay) -> Self {
+        let entries: Rc<RefCell<Vec<SpriteEntry>>> = Rc::new(RefCell::new(vec![]));
+
+        let list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::Single)
+            .build();
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .child(&list)
+            .min_content_width(320)
+            .min_content_height(400)
+            .vexpand(true)
+            .build();
+
+        let preview = gtk::Picture::builder()
+            .content_fit(gtk::ContentFit::ScaleDown)
+            .can_shrink(false)
+            .width_request(160)
+            .height_request(102)
+            .build();
+
+        let details = gtk::Label::builder()
+            .halign(gtk::Align::Start)
+            .valign(gtk::Align::Start)
+            .selectable(true)
+            .build();
+
+        let refresh_btn = gtk::Button::with_label("Refresh");
+
+        let grid = gtk::Grid::builder()
+            .margin_start(6).margin_end(6).margin_top(6).margin_bottom(6)
+            .row_spacing(6).column_spacing(6)
+            .build();
+        grid.attach(&scrolled, 0, 0, 1, 3);
+        grid.attach(&preview, 1, 0, 1, 1);
+        grid.attach(&details, 1, 1, 1, 1);
+        grid.attach(&refresh_btn, 1, 2, 1, 1);
+
+        let window = gtk::ApplicationWindow::builder()
+            .title("Suzy inspector")
+            .child(&grid)
+            .build();
+
+        refresh_btn.connect_clicked(move |_| {
+            event_tx.send(Event::RequestSnapshot).unwrap();
+        });
+
+        list.connect_row_selected(clone!(
+            #[strong] entries,
+            #[strong] display,
+            move |_, row| {
+                let entries = entries.borrow();
+                match row.and_then(|r| entries.get(r.index() as usize)) {
+                    None => {
+                        preview.set_paintable(None::<&gdk::Paintable>);
+                        details.set_text("");
+                        display.set_highlight(None);
+                    }
+                    Some(entry) => {
+                        preview.set_paintable(entry.texture.as_ref());
+                        details.set_text(&entry.details);
+                        display.set_highlight(Some(entry.rect));
+                    }
+                }
+            }
+        ));
+
+        window.connect_close_request(clone!(
+            #[strong] display,
+            move |_| {
+                display.set_highlight(None);
+                gtk::glib::Propagation::Proceed
+            }
+        ));
+
+        window.present();
+
+        Self { window, list, entries }
+    }
+}
+
+impl DebugView for SuzyInspector {
+    fn update(&mut self, snapshot: &DebugSnapshot) {
+        let selected = self.list.selected_row().map(|r| r.index());
+        let hoff = snapshot.register_word(HOFF) as i16;
+        let voff = snapshot.register_word(VOFF) as i16;
+
+        let mut entries = vec![];
+        for (chain, start) in snapshot.sprite_chains().iter().enumerate() {
+            for scb in walk_chain(snapshot, *start) {
+                let image = decode(snapshot, &scb);
+                let texture = match image.width * image.height {
+                    0 => None,
+                    _ => Some(rgba_texture(image.to_rgba(snapshot, &scb), image.width, image.height)),
+                };
+                let rect = image.screen_rect(&scb, hoff, voff);
+                entries.push(SpriteEntry {
+                    label: format!(
+                        "{}.{} ${:04X}  ({}, {})  {}x{}  {}",
+                        chain, entries.len(), scb.address, scb.hpos, scb.vpos, rect.2, rect.3, scb.sprite_type()
+                    ),
+                    details: format!(
+                        "SCB ${:04X}  next ${:04X}\nData ${:04X}\nPosition {}, {}\nSize {:04X} x {:04X}\nImage {}x{}\n{} bpp {}\nCollision #{}\nQuadrant {}\nFlags: {}\nPalette {}",
+                        scb.address,
+                        scb.next,
+                        scb.data,
+                        scb.hpos,
+                        scb.vpos,
+                        scb.hsize,
+                        scb.vsize,
+                        image.width,
+                        image.height,
+                        scb.bpp(),
+                        scb.sprite_type(),
+                        scb.collision_number(),
+                        scb.start_quadrant(),
+                        scb.flags(),
+                        scb.palette.iter().map(|p| format!("{:X}", p)).collect::<String>(),
+                    ),
+                    texture,
+                    rect,
+                });
+            }
+        }
+
+        self.list.remove_all();
+        for entry in &entries {
+            let label = gtk::Label::builder()
+                .label(&entry.label)
+                .halign(gtk::Align::Start)
+                .build();
+            self.list.append(&label);
+        }
+        self.entries.replace(entries);
+
+        if let Some(index) = selected {
+            self.list.select_row(self.list.row_at_index(index).as_ref());
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        !self.window.is_visible()
+    }
+}
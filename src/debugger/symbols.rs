@@ -0,0 +1,53 @@
+use std::{collections::BTreeMap, path::Path};
+
+/// Labels loaded from a debug symbols file, supports VICE label files as written by ld65 `-Ln`
+/// as well as `name = $addr` and `addr name` lines.
+#[derive(Default)]
+pub(crate) struct Symbols {
+    labels: BTreeMap<u16, String>,
+}
+
+fn parse_value(text: &str) -> Option<u16> {
+    let text = text.trim();
+    let hex = text
+        .strip_prefix('$')
+        .or_else(|| text.strip_prefix("0x"))
+        .unwrap_or(text);
+    u32::from_str_radix(hex, 16).ok().map(|v| (v & 0xffff) as u16)
+}
+
+impl Symbols {
+    pub(crate) fn load(path: &Path) -> std::io::Result<Self> {
+        let mut labels = BTreeMap::new();
+
+        for line in std::fs::read_to_string(path)?.lines() {
+            let line = line.split(';').next().unwrap_or("").trim();
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+
+            let symbol = match tokens.as_slice() {
+                ["al", addr, name, ..] => parse_value(addr).map(|a| (a, *name)),
+                [name, "=", value, ..] => parse_value(value).map(|a| (a, *name)),
+                [addr, name] => parse_value(addr).map(|a| (a, *name)),
+                _ => None,
+            };
+
+            if let Some((addr, name)) = symbol {
+                labels.insert(addr, name.trim_start_matches('.').to_string());
+            }
+        }
+
+        Ok(Self { labels })
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Closest label at or before `addr`.
+    pub(crate) fn routine(&self, addr: u16) -> Option<(u16, &str)> {
+        self.labels
+            .range(..=addr)
+            .next_back()
+            .map(|(a, name)| (*a, name.as_str()))
+    }
+}
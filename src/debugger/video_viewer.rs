@@ -0,0 +1,139 @@
+use gtk::prelude::*;
+use holani::mikey::video::{LYNX_SCREEN_HEIGHT, LYNX_SCREEN_WIDTH};
+use crate::Event;
+use super::{rgba_texture, DebugSnapshot, DebugView, COLLBAS, DISPADR, VIDBAS};
+
+const SWATCH_SIZE: i32 = 32;
+const BUFFER_SCALE: usize = 2;
+const BUFFER_SIZE: usize = LYNX_SCREEN_WIDTH as usize * LYNX_SCREEN_HEIGHT as usize / 2;
+
+// Distinct colors for the collision numbers, 0 being 'no collision'.
+const COLLISION_COLORS: [[u8; 4]; 16] = [
+    [0x00, 0x00, 0x00, 0xff], [0xe6, 0x19, 0x4b, 0xff], [0x3c, 0xb4, 0x4b, 0xff], [0xff, 0xe1, 0x19, 0xff],
+    [0x43, 0x63, 0xd8, 0xff], [0xf5, 0x82, 0x31, 0xff], [0x91, 0x1e, 0xb4, 0xff], [0x46, 0xf0, 0xf0, 0xff],
+    [0xf0, 0x32, 0xe6, 0xff], [0xbc, 0xf6, 0x0c, 0xff], [0xfa, 0xbe, 0xbe, 0xff], [0x00, 0x80, 0x80, 0xff],
+    [0xe6, 0xbe, 0xff, 0xff], [0x9a, 0x63, 0x24, 0xff], [0xff, 0xfa, 0xc8, 0xff], [0xff, 0xff, 0xff, 0xff],
+];
+
+struct BufferView {
+    title: &'static str,
+    register: u16,
+    label: gtk::Label,
+    picture: gtk::Picture,
+}
+
+pub(crate) struct VideoViewer {
+    window: gtk::ApplicationWindow,
+    swatches: Vec<(gtk::Picture, gtk::Label)>,
+    buffers: Vec<BufferView>,
+}
+
+impl VideoViewer {
+    pub(crate) fn new(event_tx: kanal::Sender<Event>) -> Self {
+        let grid = gtk::Grid::builder()
+            .margin_start(6).margin_end(6).margin_top(6).margin_bottom(6)
+            .row_spacing(6).column_spacing(6)
+            .build();
+
+        let palette_grid = gtk::Grid::builder()
+            .row_spacing(2).column_spacing(2)
+            .column_homogeneous(true)
+            .build();
+
+        let mut swatches = vec![];
+        for i in 0..16 {
+            let picture = gtk::Picture::builder()
+                .content_fit(gtk::ContentFit::Fill)
+                .width_request(SWATCH_SIZE)
+                .height_request(SWATCH_SIZE)
+                .build();
+            let label = gtk::Label::new(Some("000"));
+            palette_grid.attach(&gtk::Label::new(Some(format!("{:X}", i).as_str())), i, 0, 1, 1);
+            palette_grid.attach(&picture, i, 1, 1, 1);
+            palette_grid.attach(&label, i, 2, 1, 1);
+            swatches.push((picture, label));
+        }
+        grid.attach(&palette_grid, 0, 0, 3, 1);
+
+        let mut buffers = vec![];
+        for (i, (title, register)) in [("Display", DISPADR), ("Draw", VIDBAS), ("Collision", COLLBAS)].into_iter().enumerate() {
+            let label = gtk::Label::new(Some(title));
+            let picture = gtk::Picture::builder()
+                .content_fit(gtk::ContentFit::Contain)
+                .width_request((LYNX_SCREEN_WIDTH as usize * BUFFER_SCALE) as i32)
+                .height_request((LYNX_SCREEN_HEIGHT as usize * BUFFER_SCALE) as i32)
+                .build();
+            grid.attach(&label, i as i32, 1, 1, 1);
+            grid.attach(&picture, i as i32, 2, 1, 1);
+            buffers.push(BufferView { title, register, label, picture });
+        }
+
+        let refresh_btn = gtk::Button::with_label("Refresh");
+        grid.attach(&refresh_btn, 0, 3, 1, 1);
+
+        refresh_btn.connect_clicked(move |_| {
+            event_tx.send(Event::RequestSnapshot).unwrap();
+        });
+
+        let window = gtk::ApplicationWindow::builder()
+            .title("Palette and video buffers")
+            .child(&grid)
+            .build();
+
+        window.present();
+
+        Self { window, swatches, buffers }
+    }
+}
+
+/// Expands a 4 bits per pixel Lynx buffer to RGBA, scaled up with nearest neighbour.
+fn buffer_rgba(snapshot: &DebugSnapshot, address: u16, colors: &[[u8; 4]; 16]) -> Vec<u8> {
+    let width = LYNX_SCREEN_WIDTH as usize;
+    let height = LYNX_SCREEN_HEIGHT as usize;
+    let mut data = vec![0u8; width * height * 4 * BUFFER_SCALE * BUFFER_SCALE];
+
+    for i in 0..BUFFER_SIZE {
+        let byte = snapshot.ram(address.wrapping_add(i as u16));
+        for (n, pen) in [byte >> 4, byte & 0x0f].into_iter().enumerate() {
+            let x = ((i * 2) % width + n) * BUFFER_SCALE;
+            let y = (i * 2 / width) * BUFFER_SCALE;
+            for dy in 0..BUFFER_SCALE {
+                let offset = ((y + dy) * width * BUFFER_SCALE + x) * 4;
+                for dx in 0..BUFFER_SCALE {
+                    data[offset + dx * 4..offset + dx * 4 + 4].copy_from_slice(&colors[pen as usize]);
+                }
+            }
+        }
+    }
+
+    data
+}
+
+impl DebugView for VideoViewer {
+    fn update(&mut self, snapshot: &DebugSnapshot) {
+        let palette: [[u8; 4]; 16] = core::array::from_fn(|i| snapshot.palette_rgba(i as u8));
+
+        for (i, (picture, label)) in self.swatches.iter().enumerate() {
+            picture.set_paintable(Some(&rgba_texture(palette[i].to_vec(), 1, 1)));
+            label.set_text(&format!("{:03X}", snapshot.palette_entry(i as u8)));
+        }
+
+        for buffer in &self.buffers {
+            let address = snapshot.register_word(buffer.register);
+            let colors = match buffer.register {
+                COLLBAS => &COLLISION_COLORS,
+                _ => &palette,
+            };
+            buffer.label.set_text(&format!("{} ${:04X}", buffer.title, address));
+            buffer.picture.set_paintable(Some(&rgba_texture(
+                buffer_rgba(snapshot, address, colors),
+                LYNX_SCREEN_WIDTH as usize * BUFFER_SCALE,
+                LYNX_SCREEN_HEIGHT as usize * BUFFER_SCALE,
+            )));
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        !self.window.is_visible()
+    }
+}
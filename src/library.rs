@@ -0,0 +1,406 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use gtk::{gio, glib, glib::clone, prelude::*};
+use log::error;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+use crate::{cartridge::{CartInfo, MAX_CART_SIZE}, runner::runner_config::{cart_file_name, data_file}, Event};
+
+const THUMBNAIL_WIDTH: i32 = 80;
+const THUMBNAIL_HEIGHT: i32 = 51;
+
+/// Screenshot shown for a cartridge in the library.
+pub(crate) fn thumbnail_file(cart_hash: u32) -> Option<PathBuf> {
+    data_file("thumbnails", &cart_file_name(cart_hash, "png"))
+}
+
+/// When each cartridge was last played, kept apart from the settings.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct PlayHistory {
+    played: HashMap<String, u64>,
+}
+
+impl PlayHistory {
+    pub(crate) fn load() -> Self {
+        confy::load("holani-gtk", Some("library")).unwrap_or_else(|e| {
+            error!("Couldn't load the play history. '{}'", e);
+            Self::default()
+        })
+    }
+
+    pub(crate) fn played(&mut self, cart_hash: u32) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+        self.played.insert(cart_file_name(cart_hash, "lnx"), now);
+        if let Err(e) = confy::store("holani-gtk", Some("library"), &*self) {
+            error!("Couldn't save the play history. '{}'", e);
+        }
+    }
+
+    /// Unix time the cartridge was last played at.
+    pub(crate) fn last_played(&self, cart_hash: u32) -> Option<u64> {
+        self.played.get(&cart_file_name(cart_hash, "lnx")).copied()
+    }
+}
+
+/// Reads the cartridges of `folders` and their sub folders in the background, the result is sent
+/// as `Event::LibraryScanned`.
+pub(crate) fn scan(folders: Vec<PathBuf>, event_tx: kanal::Sender<Event>) {
+    std::thread::Builder::new()
+        .name("Library".to_string())
+        .spawn(move || {
+            let mut carts = vec![];
+            for folder in &folders {
+                scan_folder(folder, &mut carts);
+            }
+            let _ = event_tx.send(Event::LibraryScanned(carts));
+        })
+        .expect("Could not create the library scan thread.");
+}
+
+fn scan_folder(folder: &Path, carts: &mut Vec<CartInfo>) {
+    let entries = match std::fs::read_dir(folder) {
+        Ok(entries) => entries,
+        Err(e) => {
+            error!("Couldn't read folder '{}'. '{}'", folder.display(), e);
+            return;
+        }
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        // Symlinked folders aren't followed, they could loop back to a parent.
+        if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            scan_folder(&path, carts);
+        } else if CartInfo::is_cart_file(&path)
+            && std::fs::metadata(&path).is_ok_and(|metadata| metadata.len() <= MAX_CART_SIZE as u64)
+        {
+            if let Some(info) = CartInfo::read(&path) {
+                carts.push(info);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, EnumIter)]
+enum SortBy {
+    #[default]
+    Title,
+    Manufacturer,
+    LastPlayed,
+    FileName,
+}
+
+impl SortBy {
+    fn label(&self) -> &'static str {
+        match self {
+            SortBy::Title => "Title",
+            SortBy::Manufacturer => "Manufacturer",
+            SortBy::LastPlayed => "Last played",
+            SortBy::FileName => "File name",
+        }
+    }
+}
+
+#[derive(Default)]
+struct LibraryState {
+    carts: Vec<CartInfo>,
+    history: PlayHistory,
+    sort_by: SortBy,
+    filter: String,
+    /// Files of the listed rows, in order.
+    shown: Vec<PathBuf>,
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+fn cart_row(cart: &CartInfo, last_played: Option<u64>) -> gtk::Box {
+    let thumbnail = gtk::Picture::builder()
+        .content_fit(gtk::ContentFit::Contain)
+        .can_shrink(true)
+        .width_request(THUMBNAIL_WIDTH)
+        .height_request(THUMBNAIL_HEIGHT)
+        .build();
+    if let Some(file) = thumbnail_file(cart.hash).filter(|file| file.exists()) {
+        thumbnail.set_filename(Some(&file));
+    }
+
+    let title = gtk::Label::builder()
+        .label(&cart.title)
+        .halign(gtk::Align::Start)
+        .css_classes(["heading"])
+        .build();
+
+    let played = last_played
+        .and_then(|time| glib::DateTime::from_unix_local(time as i64).ok())
+        .and_then(|time| time.format("%Y-%m-%d %H:%M").ok())
+        .map_or("never".to_string(), |time| time.to_string());
+    let mut details = vec![];
+    if !cart.manufacturer.is_empty() {
+        details.push(cart.manufacturer.clone());
+    }
+    details.push(file_name(&cart.path));
+    details.push(format!("rotation {}", cart.rotation));
+    details.push(format!("banks {}K/{}K", cart.bank0_size / 1024, cart.bank1_size / 1024));
+    details.push(format!("played {}", played));
+    let details = gtk::Label::builder()
+        .label(details.join(" · "))
+        .halign(gtk::Align::Start)
+        .css_classes(["dim-label"])
+        .build();
+
+    let labels = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .valign(gtk::Align::Center)
+        .spacing(2)
+        .build();
+    labels.append(&title);
+    labels.append(&details);
+
+    let row = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .build();
+    row.append(&thumbnail);
+    row.append(&labels);
+    row
+}
+
+fn rebuild(list: &gtk::ListBox, state: &Rc<RefCell<LibraryState>>) {
+    list.remove_all();
+
+    let mut state = state.borrow_mut();
+    let filter = state.filter.to_lowercase();
+    let mut carts: Vec<&CartInfo> = state
+        .carts
+        .iter()
+        .filter(|cart| {
+            filter.is_empty()
+                || cart.title.to_lowercase().contains(&filter)
+                || cart.manufacturer.to_lowercase().contains(&filter)
+                || file_name(&cart.path).to_lowercase().contains(&filter)
+        })
+        .collect();
+    match state.sort_by {
+        SortBy::Title => carts.sort_by_key(|cart| cart.title.to_lowercase()),
+        SortBy::Manufacturer => carts.sort_by_key(|cart| (cart.manufacturer.to_lowercase(), cart.title.to_lowercase())),
+        SortBy::LastPlayed => carts.sort_by_key(|cart| std::cmp::Reverse(state.history.last_played(cart.hash))),
+        SortBy::FileName => carts.sort_by_key(|cart| file_name(&cart.path).to_lowercase()),
+    }
+
+    for cart in &carts {
+        list.append(&cart_row(cart, state.history.last_played(cart.hash)));
+    }
+    let shown = carts.iter().map(|cart| cart.path.clone()).collect();
+    state.shown = shown;
+}
+
+fn rebuild_folders(list: &gtk::ListBox, folders: &Rc<RefCell<Vec<PathBuf>>>, event_tx: &kanal::Sender<Event>) {
+    list.remove_all();
+
+    for (i, folder) in folders.borrow().iter().enumerate() {
+        let label = gtk::Label::builder()
+            .label(folder.display().to_string())
+            .halign(gtk::Align::Start)
+            .hexpand(true)
+            .build();
+        let remove_btn = gtk::Button::with_label("Remove");
+
+        let row = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(6)
+            .build();
+        row.append(&label);
+        row.append(&remove_btn);
+        list.append(&row);
+
+        remove_btn.connect_clicked(clone!(
+            #[strong] folders,
+            #[strong] event_tx,
+            move |_| {
+                let mut folders = folders.borrow().clone();
+                if i < folders.len() {
+                    folders.remove(i);
+                }
+                event_tx.send(Event::LibraryFolders(folders)).unwrap();
+            }
+        ));
+    }
+}
+
+/// Lists the cartridges found in the library folders, a double click loads one.
+pub(crate) struct Library {
+    window: gtk::ApplicationWindow,
+    list: gtk::ListBox,
+    folder_list: gtk::ListBox,
+    status: gtk::Label,
+    state: Rc<RefCell<LibraryState>>,
+    folders: Rc<RefCell<Vec<PathBuf>>>,
+    event_tx: kanal::Sender<Event>,
+}
+
+impl Library {
+    pub(crate) fn new(event_tx: kanal::Sender<Event>, folders: Vec<PathBuf>, history: PlayHistory) -> Self {
+        let state = Rc::new(RefCell::new(LibraryState {
+            history,
+            ..Default::default()
+        }));
+        let folders = Rc::new(RefCell::new(folders));
+
+        let folder_list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .build();
+        let add_folder_btn = gtk::Button::with_label("Add folder");
+        let rescan_btn = gtk::Button::with_label("Rescan");
+        let status = gtk::Label::builder()
+            .halign(gtk::Align::Start)
+            .hexpand(true)
+            .build();
+
+        let search = gtk::SearchEntry::builder()
+            .placeholder_text("Filter")
+            .hexpand(true)
+            .build();
+        let sorts: Vec<SortBy> = SortBy::iter().collect();
+        let labels: Vec<&str> = sorts.iter().map(|sort| sort.label()).collect();
+        let sort_dropdown = gtk::DropDown::from_strings(&labels);
+
+        let folder_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(6)
+            .build();
+        folder_box.append(&status);
+        folder_box.append(&add_folder_btn);
+        folder_box.append(&rescan_btn);
+
+        let sort_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(6)
+            .build();
+        sort_box.append(&search);
+        sort_box.append(&gtk::Label::new(Some("Sort by")));
+        sort_box.append(&sort_dropdown);
+
+        let list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::Single)
+            .activate_on_single_click(false)
+            .build();
+        let scrolled = gtk::ScrolledWindow::builder()
+            .child(&list)
+            .min_content_width(560)
+            .min_content_height(400)
+            .vexpand(true)
+            .build();
+
+        let vbox = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .margin_start(6).margin_end(6).margin_top(6).margin_bottom(6)
+            .spacing(6)
+            .build();
+        vbox.append(&folder_list);
+        vbox.append(&folder_box);
+        vbox.append(&sort_box);
+        vbox.append(&scrolled);
+
+        let window = gtk::ApplicationWindow::builder()
+            .title("Library")
+            .child(&vbox)
+            .build();
+
+        list.connect_row_activated(clone!(
+            #[strong] state,
+            #[strong] event_tx,
+            move |_, row| {
+                let path = state.borrow().shown.get(row.index() as usize).cloned();
+                if let Some(path) = path {
+                    event_tx.send(Event::LoadCart(path)).unwrap();
+                }
+            }
+        ));
+
+        search.connect_search_changed(clone!(
+            #[strong] state,
+            #[weak] list,
+            move |search| {
+                state.borrow_mut().filter = search.text().to_string();
+                rebuild(&list, &state);
+            }
+        ));
+
+        sort_dropdown.connect_selected_notify(clone!(
+            #[strong] state,
+            #[weak] list,
+            move |dropdown| {
+                state.borrow_mut().sort_by = sorts[dropdown.selected() as usize];
+                rebuild(&list, &state);
+            }
+        ));
+
+        add_folder_btn.connect_clicked(clone!(
+            #[strong] folders,
+            #[strong] event_tx,
+            #[weak] window,
+            move |_| {
+                let dialog = gtk::FileDialog::builder()
+                    .title("Add library folder")
+                    .modal(true)
+                    .build();
+                dialog.select_folder(Some(&window), gio::Cancellable::NONE, clone!(
+                    #[strong] folders,
+                    #[strong] event_tx,
+                    move |folder| {
+                        let Some(path) = folder.ok().and_then(|folder| folder.path()) else {
+                            return;
+                        };
+                        let mut folders = folders.borrow().clone();
+                        if !folders.contains(&path) {
+                            folders.push(path);
+                        }
+                        event_tx.send(Event::LibraryFolders(folders)).unwrap();
+                    }
+                ));
+            }
+        ));
+
+        rescan_btn.connect_clicked(clone!(
+            #[strong] folders,
+            #[strong] event_tx,
+            move |_| event_tx.send(Event::LibraryFolders(folders.borrow().clone())).unwrap()
+        ));
+
+        rebuild_folders(&folder_list, &folders, &event_tx);
+        status.set_label("Scanning…");
+
+        window.present();
+
+        Self { window, list, folder_list, status, state, folders, event_tx }
+    }
+
+    pub(crate) fn set_folders(&self, folders: Vec<PathBuf>) {
+        self.folders.replace(folders);
+        rebuild_folders(&self.folder_list, &self.folders, &self.event_tx);
+        self.status.set_label("Scanning…");
+    }
+
+    pub(crate) fn set_carts(&self, carts: Vec<CartInfo>) {
+        self.status.set_label(&format!("{} cartridges", carts.len()));
+        self.state.borrow_mut().carts = carts;
+        rebuild(&self.list, &self.state);
+    }
+
+    pub(crate) fn set_history(&self, history: PlayHistory) {
+        self.state.borrow_mut().history = history;
+        rebuild(&self.list, &self.state);
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        !self.window.is_visible()
+    }
+}
@@ -0,0 +1,48 @@
+use crate::runner::runner_config::ColorAdjustments;
+
+/// Number of colors the Lynx can show, 4 bits per channel.
+const LYNX_COLORS: usize = 4096;
+const RED_LUMA: f32 = 0.299;
+const GREEN_LUMA: f32 = 0.587;
+const BLUE_LUMA: f32 = 0.114;
+
+fn lut_index(r: u8, g: u8, b: u8) -> usize {
+    ((r as usize >> 4) << 8) | ((g as usize >> 4) << 4) | (b as usize >> 4)
+}
+
+/// Maps every Lynx color to its corrected RGB value.
+pub struct ColorCorrection {
+    lut: Vec<[u8; 3]>,
+}
+
+impl ColorCorrection {
+    pub fn new(adjustments: &ColorAdjustments) -> Self {
+        let lut = (0..LYNX_COLORS)
+            .map(|index| {
+                let channel = |shift: usize| ((index >> shift) & 0x0f) as f32 / 15.;
+                correct(adjustments, [channel(8), channel(4), channel(0)])
+            })
+            .collect();
+        Self { lut }
+    }
+
+    /// Corrects an RGBA frame in place.
+    pub fn apply(&self, frame: &mut [u8]) {
+        for pixel in frame.chunks_exact_mut(4) {
+            let [r, g, b] = self.lut[lut_index(pixel[0], pixel[1], pixel[2])];
+            pixel[0] = r;
+            pixel[1] = g;
+            pixel[2] = b;
+        }
+    }
+}
+
+fn correct(adjustments: &ColorAdjustments, rgb: [f32; 3]) -> [u8; 3] {
+    let luma = rgb[0] * RED_LUMA + rgb[1] * GREEN_LUMA + rgb[2] * BLUE_LUMA;
+    rgb.map(|c| {
+        let c = luma + (c - luma) * adjustments.saturation;
+        let c = (c - 0.5) * adjustments.contrast + 0.5;
+        let c = (c + adjustments.brightness).clamp(0., 1.);
+        (c.powf(adjustments.gamma.max(0.01)) * 255.).round() as u8
+    })
+}
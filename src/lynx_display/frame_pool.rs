@@ -0,0 +1,46 @@
+use std::sync::{Arc, Mutex};
+
+/// Buffers kept for later frames, more are only needed while GTK holds on to old textures.
+const MAX_POOLED_FRAMES: usize = 4;
+
+/// Pixel buffers of the textures shown by a display. A texture owns its pixels, the buffer goes
+/// back to the pool once GTK drops the texture instead of being freed.
+#[derive(Clone, Default)]
+pub struct FramePool {
+    buffers: Arc<Mutex<Vec<Vec<u8>>>>,
+}
+
+impl FramePool {
+    /// Empty buffer, with the memory of a released frame when one is available.
+    pub fn take(&self) -> PooledFrame {
+        let mut data = self.buffers.lock().unwrap().pop().unwrap_or_default();
+        data.clear();
+        PooledFrame { data, pool: self.clone() }
+    }
+}
+
+pub struct PooledFrame {
+    data: Vec<u8>,
+    pool: FramePool,
+}
+
+impl PooledFrame {
+    pub fn data_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.data
+    }
+}
+
+impl AsRef<[u8]> for PooledFrame {
+    fn as_ref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl Drop for PooledFrame {
+    fn drop(&mut self) {
+        let mut buffers = self.pool.buffers.lock().unwrap();
+        if buffers.len() < MAX_POOLED_FRAMES {
+            buffers.push(std::mem::take(&mut self.data));
+        }
+    }
+}
@@ -0,0 +1,32 @@
+/// LCD persistence, each frame is blended with the previously displayed one so flickering
+/// sprites end up half transparent like on the real screen.
+#[derive(Default)]
+pub struct Ghosting {
+    /// Share of the previous frame kept, in percent.
+    persistence: u16,
+    previous: Vec<u8>,
+}
+
+impl Ghosting {
+    pub fn set_persistence(&mut self, persistence: u8) {
+        self.persistence = persistence.min(95) as u16;
+        self.previous.clear();
+    }
+
+    /// Blends the frame in place with the previously displayed one.
+    pub fn apply(&mut self, frame: &mut [u8]) {
+        if self.persistence == 0 {
+            return;
+        }
+        if self.previous.len() != frame.len() {
+            self.previous.clear();
+            self.previous.extend_from_slice(frame);
+            return;
+        }
+        let p = self.persistence;
+        for (prev, cur) in self.previous.iter_mut().zip(frame.iter_mut()) {
+            *prev = ((*prev as u16 * p + *cur as u16 * (100 - p) + 50) / 100) as u8;
+            *cur = *prev;
+        }
+    }
+}
@@ -0,0 +1,343 @@
+use std::{
+    cell::RefCell,
+    ffi::c_void,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::OnceLock,
+};
+
+use glow::HasContext as _;
+use gtk::{gdk, glib, glib::clone, prelude::*};
+use holani::mikey::video::{LYNX_SCREEN_HEIGHT, LYNX_SCREEN_WIDTH};
+use log::error;
+
+use super::LynxDisplay;
+use crate::runner::runner_config::{data_dir, Scaler};
+
+const SHADERS_FOLDER: &str = "shaders";
+const SHADER_EXTENSION: &str = "glsl";
+/// Examples written to the shaders directory when it is empty.
+const BUNDLED_SHADERS: [(&str, &str); 3] = [
+    ("crt.glsl", include_str!("../../shaders/crt.glsl")),
+    ("lcd.glsl", include_str!("../../shaders/lcd.glsl")),
+    ("scanlines.glsl", include_str!("../../shaders/scanlines.glsl")),
+];
+
+#[cfg(target_os = "macos")]
+const EPOXY_LIBRARY: &str = "libepoxy.0.dylib";
+#[cfg(windows)]
+const EPOXY_LIBRARY: &str = "libepoxy-0.dll";
+#[cfg(all(unix, not(target_os = "macos")))]
+const EPOXY_LIBRARY: &str = "libepoxy.so.0";
+
+const VERTEX_SHADER: &str = "out vec2 v_tex;
+void main() {
+    vec2 position = vec2(float(gl_VertexID & 1), float(gl_VertexID >> 1));
+    v_tex = vec2(position.x, 1.0 - position.y);
+    gl_Position = vec4(position * 2.0 - 1.0, 0.0, 1.0);
+}
+";
+
+/// Declarations available to the post-processing shaders.
+const FRAGMENT_HEADER: &str = "in vec2 v_tex;
+out vec4 frag_color;
+uniform sampler2D u_frame;
+uniform vec2 u_source_size;
+uniform vec2 u_texture_size;
+uniform vec2 u_output_size;
+#line 1
+";
+
+const PASSTHROUGH_SHADER: &str = "void main() {
+    frag_color = texture(u_frame, v_tex);
+}
+";
+
+static EPOXY: OnceLock<Result<libloading::Library, String>> = OnceLock::new();
+
+/// GTK resolves the GL entry points through libepoxy, so does glow.
+fn load_epoxy() -> Result<(), String> {
+    EPOXY
+        .get_or_init(|| {
+            let library = unsafe { libloading::Library::new(EPOXY_LIBRARY) }.map_err(|e| e.to_string())?;
+            epoxy::load_with(|name| {
+                unsafe { library.get::<*const c_void>(name.as_bytes()) }
+                    .map(|symbol| *symbol)
+                    .unwrap_or(std::ptr::null())
+            });
+            Ok(library)
+        })
+        .as_ref()
+        .map(|_| ())
+        .map_err(|e| e.clone())
+}
+
+/// Post-processing shaders found in the shaders directory, by file name.
+pub fn shaders() -> Vec<String> {
+    let Some(dir) = data_dir(SHADERS_FOLDER) else {
+        return vec![];
+    };
+    if std::fs::read_dir(&dir).is_ok_and(|mut entries| entries.next().is_none()) {
+        for (name, source) in BUNDLED_SHADERS {
+            if let Err(e) = std::fs::write(dir.join(name), source) {
+                error!("Couldn't write shader '{}'. '{}'", name, e);
+            }
+        }
+    }
+    let mut shaders: Vec<String> = std::fs::read_dir(&dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == SHADER_EXTENSION))
+                .filter_map(|path| Some(path.file_name()?.to_string_lossy().to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    shaders.sort();
+    shaders
+}
+
+pub fn shader_path(name: &str) -> Option<PathBuf> {
+    Some(data_dir(SHADERS_FOLDER)?.join(name))
+}
+
+fn version_header(es: bool) -> &'static str {
+    match es {
+        true => "#version 300 es\nprecision highp float;\n",
+        false => "#version 150\n",
+    }
+}
+
+unsafe fn compile_shader(gl: &glow::Context, kind: u32, source: &str) -> Result<glow::Shader, String> {
+    let shader = gl.create_shader(kind)?;
+    gl.shader_source(shader, source);
+    gl.compile_shader(shader);
+    if !gl.get_shader_compile_status(shader) {
+        let log = gl.get_shader_info_log(shader);
+        gl.delete_shader(shader);
+        return Err(log);
+    }
+    Ok(shader)
+}
+
+fn link_program(gl: &glow::Context, es: bool, fragment_source: &str) -> Result<glow::Program, String> {
+    unsafe {
+        let vertex = compile_shader(gl, glow::VERTEX_SHADER, &format!("{}{}", version_header(es), VERTEX_SHADER))?;
+        let fragment = match compile_shader(
+            gl,
+            glow::FRAGMENT_SHADER,
+            &format!("{}{}{}", version_header(es), FRAGMENT_HEADER, fragment_source),
+        ) {
+            Ok(fragment) => fragment,
+            Err(e) => {
+                gl.delete_shader(vertex);
+                return Err(e);
+            }
+        };
+        let program = gl.create_program()?;
+        gl.attach_shader(program, vertex);
+        gl.attach_shader(program, fragment);
+        gl.link_program(program);
+        gl.detach_shader(program, vertex);
+        gl.detach_shader(program, fragment);
+        gl.delete_shader(vertex);
+        gl.delete_shader(fragment);
+        if !gl.get_program_link_status(program) {
+            let log = gl.get_program_info_log(program);
+            gl.delete_program(program);
+            return Err(log);
+        }
+        Ok(program)
+    }
+}
+
+/// Draws the frames of a display with a post-processing shader, in the GL context of a `GLArea`.
+struct GlRenderer {
+    gl: glow::Context,
+    program: glow::Program,
+    vertex_array: glow::VertexArray,
+    texture: glow::Texture,
+}
+
+impl GlRenderer {
+    /// Needs the area GL context to be current. A shader that doesn't build is replaced by a
+    /// passthrough one, its error is returned along the renderer.
+    fn new(es: bool, shader: Option<&Path>) -> Result<(Self, Option<String>), String> {
+        load_epoxy()?;
+        let gl = unsafe { glow::Context::from_loader_function(epoxy::get_proc_addr) };
+
+        let user_program = shader.map(|path| {
+            std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|source| link_program(&gl, es, &source))
+        });
+        let (program, shader_error) = match user_program {
+            Some(Ok(program)) => (program, None),
+            Some(Err(e)) => (link_program(&gl, es, PASSTHROUGH_SHADER)?, Some(e)),
+            None => (link_program(&gl, es, PASSTHROUGH_SHADER)?, None),
+        };
+
+        let (vertex_array, texture) = unsafe { (gl.create_vertex_array()?, gl.create_texture()?) };
+
+        Ok((
+            Self {
+                gl,
+                program,
+                vertex_array,
+                texture,
+            },
+            shader_error,
+        ))
+    }
+
+    /// `viewport` is the screen area in device pixels, from the bottom left corner.
+    fn draw(&self, display: &LynxDisplay, viewport: (i32, i32, i32, i32)) {
+        let gl = &self.gl;
+        unsafe {
+            gl.clear_color(0., 0., 0., 1.);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+
+            let Some((frame, width, height)) = display.frame() else {
+                return;
+            };
+
+            let filter = match display.scaler() {
+                Scaler::Bilinear => glow::LINEAR,
+                _ => glow::NEAREST,
+            } as i32;
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_texture(glow::TEXTURE_2D, Some(self.texture));
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, filter);
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, filter);
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE as i32);
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE as i32);
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::RGBA8 as i32,
+                width as i32,
+                height as i32,
+                0,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelUnpackData::Slice(Some(&frame)),
+            );
+
+            let (x, y, w, h) = viewport;
+            gl.viewport(x, y, w, h);
+            gl.use_program(Some(self.program));
+            let uniform = |name: &str| gl.get_uniform_location(self.program, name);
+            gl.uniform_1_i32(uniform("u_frame").as_ref(), 0);
+            gl.uniform_2_f32(uniform("u_source_size").as_ref(), LYNX_SCREEN_WIDTH as f32, LYNX_SCREEN_HEIGHT as f32);
+            gl.uniform_2_f32(uniform("u_texture_size").as_ref(), width as f32, height as f32);
+            gl.uniform_2_f32(uniform("u_output_size").as_ref(), w as f32, h as f32);
+            gl.bind_vertex_array(Some(self.vertex_array));
+            gl.draw_arrays(glow::TRIANGLE_STRIP, 0, 4);
+            gl.bind_vertex_array(None);
+            gl.use_program(None);
+        }
+    }
+
+    /// Needs the area GL context to be current.
+    fn destroy(self) {
+        unsafe {
+            self.gl.delete_program(self.program);
+            self.gl.delete_vertex_array(self.vertex_array);
+            self.gl.delete_texture(self.texture);
+        }
+    }
+}
+
+/// Screen widget rendering the frames with OpenGL. The display paintable is laid over it to draw
+/// the grid, the highlight and the OSD. `on_error` is called when GL can't be used.
+pub fn gl_display_widget(
+    display: &LynxDisplay,
+    picture: gtk::Picture,
+    shader: Option<PathBuf>,
+    on_error: impl Fn(String) + 'static,
+) -> gtk::Widget {
+    let area = gtk::GLArea::builder()
+        .hexpand(true)
+        .vexpand(true)
+        .allowed_apis(gdk::GLAPI::GL | gdk::GLAPI::GLES)
+        .build();
+
+    let renderer: Rc<RefCell<Option<GlRenderer>>> = Rc::new(RefCell::new(None));
+    let invalidate_handler: Rc<RefCell<Option<glib::SignalHandlerId>>> = Rc::new(RefCell::new(None));
+
+    area.connect_realize(clone!(
+        #[weak] display,
+        #[strong] renderer,
+        #[strong] invalidate_handler,
+        move |area| {
+            area.make_current();
+            if let Some(e) = area.error() {
+                on_error(e.to_string());
+                return;
+            }
+            let es = area.context().is_some_and(|context| context.uses_es());
+            match GlRenderer::new(es, shader.as_deref()) {
+                Ok((gl_renderer, shader_error)) => {
+                    if let Some(e) = shader_error {
+                        error!("Couldn't build shader. '{}'", e);
+                        display.show_message("Shader error, see the log".to_string());
+                    }
+                    renderer.replace(Some(gl_renderer));
+                }
+                Err(e) => {
+                    on_error(e);
+                    return;
+                }
+            }
+            invalidate_handler.replace(Some(display.connect_invalidate_contents(clone!(
+                #[weak] area,
+                move |_| area.queue_render()
+            ))));
+        }
+    ));
+
+    area.connect_unrealize(clone!(
+        #[weak] display,
+        #[strong] renderer,
+        #[strong] invalidate_handler,
+        move |area| {
+            if let Some(handler) = invalidate_handler.take() {
+                display.disconnect(handler);
+            }
+            if let Some(gl_renderer) = renderer.take() {
+                area.make_current();
+                gl_renderer.destroy();
+            }
+        }
+    ));
+
+    area.connect_render(clone!(
+        #[weak] display,
+        #[strong] renderer,
+        #[upgrade_or] glib::Propagation::Proceed,
+        move |area, _| {
+            let Some(gl_renderer) = &*renderer.borrow() else {
+                return glib::Propagation::Proceed;
+            };
+            let scale = area.scale_factor() as f32;
+            let (width, height) = (area.width() as f32, area.height() as f32);
+            let screen = display.screen_area(width, height);
+            let viewport = (
+                (screen.x() * scale).round() as i32,
+                ((height - screen.y() - screen.height()) * scale).round() as i32,
+                (screen.width() * scale).round() as i32,
+                (screen.height() * scale).round() as i32,
+            );
+            gl_renderer.draw(&display, viewport);
+            glib::Propagation::Stop
+        }
+    ));
+
+    display.set_overlay_only(true);
+    picture.set_can_target(false);
+
+    let overlay = gtk::Overlay::builder().child(&area).build();
+    overlay.add_overlay(&picture);
+    overlay.upcast()
+}
@@ -1,10 +1,39 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
-use gtk::{gdk, glib, graphene, prelude::*, subclass::prelude::*};
+use gtk::{gdk, glib, graphene, gsk, pango, prelude::*, subclass::prelude::*};
+use holani::mikey::video::{LYNX_SCREEN_HEIGHT, LYNX_SCREEN_WIDTH};
+use crate::runner::runner_config::Scaler;
+
+const HIGHLIGHT_COLOR: gdk::RGBA = gdk::RGBA::new(1., 0., 1., 1.);
+const GRID_COLOR: gdk::RGBA = gdk::RGBA::new(0., 0., 0., 0.35);
+/// Smallest Lynx pixel size, in screen pixels, the LCD grid is drawn at.
+const GRID_MIN_SCALE: f32 = 3.;
+/// Size the display asks for, in Lynx pixels multiples.
+const DEFAULT_SCALE: i32 = 2;
+const OSD_COLOR: gdk::RGBA = gdk::RGBA::new(1., 1., 1., 0.9);
+const OSD_SHADOW_COLOR: gdk::RGBA = gdk::RGBA::new(0., 0., 0., 0.9);
+const OSD_FONT: &str = "Monospace Bold";
+/// OSD text height as a fraction of the screen height, never smaller than `OSD_MIN_FONT_SIZE`.
+const OSD_LINES_PER_SCREEN: f32 = 16.;
+const OSD_MIN_FONT_SIZE: f32 = 9.;
 
 #[derive(Default)]
 pub struct LynxDisplay {
     pub next_frame: RefCell<Option<gdk::Texture>>,
+    /// Pixels of `next_frame`, with its width and height.
+    pub frame: RefCell<Option<(glib::Bytes, usize, usize)>>,
+    pub overlay_only: Cell<bool>,
+    pub highlight: RefCell<Option<graphene::Rect>>,
+    pub last_frame: RefCell<Vec<u8>>,
+    /// Filtered frame waiting for a CPU scaler, kept to reuse its memory.
+    pub filtered: RefCell<Vec<u8>>,
+    pub frame_pool: super::frame_pool::FramePool,
+    pub color: RefCell<Option<super::color::ColorCorrection>>,
+    pub ghosting: RefCell<super::ghosting::Ghosting>,
+    pub scaler: Cell<Scaler>,
+    pub lcd_grid: Cell<bool>,
+    pub osd: RefCell<super::osd::Osd>,
+    pub pango_context: RefCell<Option<pango::Context>>,
 }
 
 #[glib::object_subclass]
@@ -16,12 +45,71 @@ impl ObjectSubclass for LynxDisplay {
 
 impl ObjectImpl for LynxDisplay {}
 
+impl LynxDisplay {
+    /// Area the Lynx screen is drawn in.
+    pub fn screen_rect(&self, width: f32, height: f32) -> graphene::Rect {
+        let (w, h) = (LYNX_SCREEN_WIDTH as f32, LYNX_SCREEN_HEIGHT as f32);
+        if self.scaler.get() != Scaler::Integer {
+            return graphene::Rect::new(0., 0., width, height);
+        }
+        let scale = (width / w).min(height / h).floor();
+        if scale < 1. {
+            return graphene::Rect::new(0., 0., width, height);
+        }
+        let (sw, sh) = (w * scale, h * scale);
+        graphene::Rect::new(((width - sw) / 2.).floor(), ((height - sh) / 2.).floor(), sw, sh)
+    }
+
+    fn draw_grid(&self, snapshot: &gtk::Snapshot, screen: &graphene::Rect) {
+        let sx = screen.width() / LYNX_SCREEN_WIDTH as f32;
+        let sy = screen.height() / LYNX_SCREEN_HEIGHT as f32;
+        if sx.min(sy) < GRID_MIN_SCALE {
+            return;
+        }
+        let thickness = (sx.min(sy) / 4.).floor().max(1.);
+        for x in 1..LYNX_SCREEN_WIDTH {
+            let x = screen.x() + (x as f32 * sx).round();
+            snapshot.append_color(&GRID_COLOR, &graphene::Rect::new(x, screen.y(), thickness, screen.height()));
+        }
+        for y in 1..LYNX_SCREEN_HEIGHT {
+            let y = screen.y() + (y as f32 * sy).round();
+            snapshot.append_color(&GRID_COLOR, &graphene::Rect::new(screen.x(), y, screen.width(), thickness));
+        }
+    }
+
+    fn draw_osd(&self, snapshot: &gtk::Snapshot, screen: &graphene::Rect) {
+        let lines = self.osd.borrow().lines();
+        if lines.is_empty() {
+            return;
+        }
+        let Some(context) = &*self.pango_context.borrow() else {
+            return;
+        };
+
+        let size = (screen.height() / OSD_LINES_PER_SCREEN).max(OSD_MIN_FONT_SIZE);
+        let mut font = pango::FontDescription::from_string(OSD_FONT);
+        font.set_absolute_size(size as f64 * pango::SCALE as f64);
+        let layout = pango::Layout::new(context);
+        layout.set_font_description(Some(&font));
+        layout.set_text(&lines.join("\n"));
+
+        let margin = (size / 2.).floor();
+        let shadow = (size / 10.).ceil();
+        for (offset, color) in [(shadow, OSD_SHADOW_COLOR), (0., OSD_COLOR)] {
+            snapshot.save();
+            snapshot.translate(&graphene::Point::new(screen.x() + margin + offset, screen.y() + margin + offset));
+            snapshot.append_layout(&layout, &color);
+            snapshot.restore();
+        }
+    }
+}
+
 impl PaintableImpl for LynxDisplay {
     fn intrinsic_height(&self) -> i32 {
         self.next_frame
             .borrow()
             .as_ref()
-            .map(|texture| texture.height())
+            .map(|_| LYNX_SCREEN_HEIGHT as i32 * DEFAULT_SCALE)
             .unwrap_or(-1)
     }
 
@@ -29,18 +117,42 @@ impl PaintableImpl for LynxDisplay {
         self.next_frame
             .borrow()
             .as_ref()
-            .map(|texture| texture.width())
+            .map(|_| LYNX_SCREEN_WIDTH as i32 * DEFAULT_SCALE)
             .unwrap_or(-1)
     }
 
     fn snapshot(&self, snapshot: &gdk::Snapshot, width: f64, height: f64) {
-        if let Some(texture) = &*self.next_frame.borrow() {
-            texture.snapshot(snapshot, width, height);
-        } else {
-            snapshot.append_color(
-                &gdk::RGBA::BLACK,
-                &graphene::Rect::new(0f32, 0f32, width as f32, height as f32),
+        let snapshot = snapshot.downcast_ref::<gtk::Snapshot>().unwrap();
+        let bounds = graphene::Rect::new(0f32, 0f32, width as f32, height as f32);
+        let screen = self.screen_rect(width as f32, height as f32);
+
+        if !self.overlay_only.get() {
+            snapshot.append_color(&gdk::RGBA::BLACK, &bounds);
+        }
+        if let Some(texture) = self.next_frame.borrow().as_ref().filter(|_| !self.overlay_only.get()) {
+            let filter = match self.scaler.get() {
+                Scaler::Bilinear => gsk::ScalingFilter::Linear,
+                _ => gsk::ScalingFilter::Nearest,
+            };
+            snapshot.append_scaled_texture(texture, filter, &screen);
+        }
+
+        if self.lcd_grid.get() {
+            self.draw_grid(snapshot, &screen);
+        }
+
+        if let Some(rect) = &*self.highlight.borrow() {
+            let sx = screen.width() / LYNX_SCREEN_WIDTH as f32;
+            let sy = screen.height() / LYNX_SCREEN_HEIGHT as f32;
+            let bounds = graphene::Rect::new(
+                screen.x() + rect.x() * sx,
+                screen.y() + rect.y() * sy,
+                rect.width() * sx,
+                rect.height() * sy,
             );
+            snapshot.append_border(&gsk::RoundedRect::from_rect(bounds, 0.), &[2.; 4], &[HIGHLIGHT_COLOR; 4]);
         }
+
+        self.draw_osd(snapshot, &screen);
     }
-}
\ No newline at end of file
+}
@@ -1,7 +1,16 @@
+mod color;
+mod frame_pool;
+mod ghosting;
+mod gl_renderer;
 mod imp;
+mod osd;
+mod scaler;
 
-use gtk::{gdk, glib, prelude::*, subclass::prelude::*};
+pub use gl_renderer::{gl_display_widget, shader_path, shaders};
+
+use gtk::{gdk, glib, glib::clone, graphene, pango, prelude::*, subclass::prelude::*};
 use holani::mikey::video::{LYNX_SCREEN_HEIGHT, LYNX_SCREEN_WIDTH};
+use crate::runner::{runner_config::{ColorAdjustments, OsdElement, Scaler}, stats::RunnerStats};
 
 glib::wrapper! {
     pub struct LynxDisplay(ObjectSubclass<imp::LynxDisplay>) @implements gdk::Paintable;
@@ -15,30 +24,159 @@ impl Default for LynxDisplay {
 
 impl LynxDisplay {
     pub fn setup_next_frame(&self, data: &[u8]) {
+        let mut last_frame = self.imp().last_frame.borrow_mut();
+        last_frame.clear();
+        last_frame.extend_from_slice(data);
+        drop(last_frame);
+        self.render(data);
+    }
+
+    /// Runs the color correction and the ghosting on a copy of the frame made in `dst`.
+    fn filter(&self, data: &[u8], dst: &mut Vec<u8>) {
+        let imp = self.imp();
+        dst.clear();
+        dst.extend_from_slice(data);
+        if let Some(color) = &*imp.color.borrow() {
+            color.apply(dst);
+        }
+        imp.ghosting.borrow_mut().apply(dst);
+    }
+
+    fn render(&self, data: &[u8]) {
         let imp = self.imp();
+        let scaler = imp.scaler.get();
+        let (width, height) = (LYNX_SCREEN_WIDTH as usize, LYNX_SCREEN_HEIGHT as usize);
 
-        let bytes = glib::Bytes::from(data);
+        // Frames shown as is are filtered straight into the texture pixels.
+        let mut frame = imp.frame_pool.take();
+        if scaler::factor(scaler) == 1 {
+            self.filter(data, frame.data_mut());
+        } else {
+            let mut filtered = imp.filtered.borrow_mut();
+            self.filter(data, &mut filtered);
+            scaler::upscale(scaler, &filtered, width, height, frame.data_mut());
+        }
+        let (width, height) = (width * scaler::factor(scaler), height * scaler::factor(scaler));
 
-        let pixbuf = gtk::gdk_pixbuf::Pixbuf::from_bytes(
+        let bytes = glib::Bytes::from_owned(frame);
+        let texture = gdk::MemoryTexture::new(
+            width as i32,
+            height as i32,
+            gdk::MemoryFormat::R8g8b8a8,
             &bytes,
-            gtk::gdk_pixbuf::Colorspace::Rgb,
-            true,
-            8,
-            LYNX_SCREEN_WIDTH as i32,
-            LYNX_SCREEN_HEIGHT as i32,
-            LYNX_SCREEN_WIDTH as i32 * 4,
-        )
-        .scale_simple(
-            LYNX_SCREEN_WIDTH as i32 * 2,
-            LYNX_SCREEN_HEIGHT as i32 * 2,
-            gtk::gdk_pixbuf::InterpType::Nearest,
-        )
-        .unwrap();
-
-        let texture = gdk::Texture::for_pixbuf(&pixbuf);
-
-        imp.next_frame.replace(Some(texture));
+            width * 4,
+        );
+
+        imp.next_frame.replace(Some(texture.upcast()));
+        imp.frame.replace(Some((bytes, width, height)));
+
+        self.invalidate_contents();
+    }
+
+    /// Color correction applied to the frames, `None` shows the raw colors. The last frame is
+    /// drawn again so the change is visible while paused.
+    pub fn set_color_correction(&self, adjustments: Option<ColorAdjustments>) {
+        let imp = self.imp();
+        imp.color.replace(adjustments.map(|adjustments| color::ColorCorrection::new(&adjustments)));
+        let frame = imp.last_frame.take();
+        if !frame.is_empty() {
+            self.render(&frame);
+        }
+        imp.last_frame.replace(frame);
+    }
+
+    /// Saves the current frame as a PNG file.
+    pub fn save_screenshot(&self, file: &std::path::Path) -> Result<(), glib::BoolError> {
+        match &*self.imp().next_frame.borrow() {
+            Some(texture) => texture.save_to_png(file),
+            None => Err(glib::bool_error!("No frame to save")),
+        }
+    }
+
+    /// Last frame pixels after the filters and the scaler, with its width and height.
+    pub fn frame(&self) -> Option<(glib::Bytes, usize, usize)> {
+        self.imp().frame.borrow().clone()
+    }
+
+    pub fn scaler(&self) -> Scaler {
+        self.imp().scaler.get()
+    }
+
+    /// Area the Lynx screen takes in a widget of the given size showing the display.
+    pub fn screen_area(&self, width: f32, height: f32) -> graphene::Rect {
+        let ratio = LYNX_SCREEN_WIDTH as f32 / LYNX_SCREEN_HEIGHT as f32;
+        let (w, h) = match width / height > ratio {
+            true => (height * ratio, height),
+            false => (width, width / ratio),
+        };
+        let (x, y) = (((width - w) / 2.).floor(), ((height - h) / 2.).floor());
+        let screen = self.imp().screen_rect(w, h);
+        graphene::Rect::new(x + screen.x(), y + screen.y(), screen.width(), screen.height())
+    }
+
+    /// Leaves the frame out and only draws the overlays, the frame being rendered below by GL.
+    pub fn set_overlay_only(&self, overlay_only: bool) {
+        self.imp().overlay_only.set(overlay_only);
+        self.invalidate_contents();
+    }
+
+    /// Share of the previous frame blended in the new one, in percent, 0 disables the ghosting.
+    pub fn set_ghosting(&self, persistence: u8) {
+        self.imp().ghosting.borrow_mut().set_persistence(persistence);
+    }
+
+    pub fn set_scaler(&self, scaler: Scaler) {
+        self.imp().scaler.set(scaler);
+        self.invalidate_contents();
+    }
+
+    /// Draws the gaps between the LCD pixels when the screen is large enough.
+    pub fn set_lcd_grid(&self, lcd_grid: bool) {
+        self.imp().lcd_grid.set(lcd_grid);
+        self.invalidate_contents();
+    }
+
+    /// Context the OSD text is laid out with, taken from the widget showing the display.
+    pub fn set_pango_context(&self, context: &pango::Context) {
+        self.imp().pango_context.replace(Some(context.clone()));
+    }
+
+    pub fn set_osd(&self, element: OsdElement, shown: bool) {
+        self.imp().osd.borrow_mut().set_shown(element, shown);
+        self.invalidate_contents();
+    }
+
+    pub fn set_stats(&self, stats: RunnerStats) {
+        let mut osd = self.imp().osd.borrow_mut();
+        osd.set_stats(stats);
+        let visible = [OsdElement::Fps, OsdElement::Speed, OsdElement::AudioBuffer]
+            .into_iter()
+            .any(|element| osd.is_shown(element));
+        drop(osd);
+        if visible {
+            self.invalidate_contents();
+        }
+    }
+
+    /// Shows a message for a few seconds.
+    pub fn show_message(&self, message: String) {
+        self.imp().osd.borrow_mut().set_message(message);
+        self.invalidate_contents();
+        glib::timeout_add_local_once(
+            osd::MESSAGE_DURATION,
+            clone!(
+                #[weak(rename_to = display)]
+                self,
+                move || display.invalidate_contents()
+            ),
+        );
+    }
 
+    /// Outlines an area of the Lynx screen, in Lynx pixels.
+    pub fn set_highlight(&self, rect: Option<(f32, f32, f32, f32)>) {
+        self.imp()
+            .highlight
+            .replace(rect.map(|(x, y, w, h)| graphene::Rect::new(x, y, w, h)));
         self.invalidate_contents();
     }
 }
@@ -0,0 +1,57 @@
+use std::time::{Duration, Instant};
+
+use crate::runner::{runner_config::OsdElement, stats::RunnerStats};
+
+/// How long a message stays on screen.
+pub(super) const MESSAGE_DURATION: Duration = Duration::from_secs(3);
+
+/// Text drawn over the Lynx screen.
+#[derive(Default)]
+pub struct Osd {
+    shown: Vec<OsdElement>,
+    stats: Option<RunnerStats>,
+    message: Option<(String, Instant)>,
+}
+
+impl Osd {
+    pub fn is_shown(&self, element: OsdElement) -> bool {
+        self.shown.contains(&element)
+    }
+
+    pub fn set_shown(&mut self, element: OsdElement, shown: bool) {
+        self.shown.retain(|e| *e != element);
+        if shown {
+            self.shown.push(element);
+        }
+    }
+
+    pub fn set_stats(&mut self, stats: RunnerStats) {
+        self.stats = Some(stats);
+    }
+
+    pub fn set_message(&mut self, message: String) {
+        self.message = Some((message, Instant::now() + MESSAGE_DURATION));
+    }
+
+    /// Lines to draw, top to bottom.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![];
+        if let Some(stats) = &self.stats {
+            if self.is_shown(OsdElement::Fps) {
+                lines.push(format!("{:.1}/{:.1} FPS", stats.fps, stats.refresh_rate));
+            }
+            if self.is_shown(OsdElement::Speed) {
+                lines.push(format!("Speed {:.0}%", stats.speed()));
+            }
+            if self.is_shown(OsdElement::AudioBuffer) {
+                lines.push(format!("Audio {:.0}%", stats.audio_fill * 100.));
+            }
+        }
+        if self.is_shown(OsdElement::Messages) {
+            if let Some((message, _)) = self.message.as_ref().filter(|(_, until)| *until > Instant::now()) {
+                lines.push(message.clone());
+            }
+        }
+        lines
+    }
+}
@@ -0,0 +1,111 @@
+use crate::runner::runner_config::Scaler;
+
+fn pixel(data: &[u8], index: usize) -> u32 {
+    let p = &data[index * 4..index * 4 + 4];
+    u32::from_ne_bytes([p[0], p[1], p[2], p[3]])
+}
+
+fn put_pixel(data: &mut [u8], index: usize, pixel: u32) {
+    data[index * 4..index * 4 + 4].copy_from_slice(&pixel.to_ne_bytes());
+}
+
+fn blend(a: u32, b: u32) -> u32 {
+    let (a, b) = (a.to_ne_bytes(), b.to_ne_bytes());
+    u32::from_ne_bytes(std::array::from_fn(|i| ((a[i] as u16 + b[i] as u16) / 2) as u8))
+}
+
+/// 3x3 neighbourhood of a pixel, edges are clamped.
+/// ```text
+/// A B C
+/// D E F
+/// G H I
+/// ```
+struct Neighbours {
+    a: u32, b: u32, c: u32,
+    d: u32, e: u32, f: u32,
+    g: u32, h: u32, i: u32,
+}
+
+fn neighbours(src: &[u8], width: usize, height: usize, x: usize, y: usize) -> Neighbours {
+    let px = |dx: isize, dy: isize| {
+        let x = (x as isize + dx).clamp(0, width as isize - 1) as usize;
+        let y = (y as isize + dy).clamp(0, height as isize - 1) as usize;
+        pixel(src, y * width + x)
+    };
+    Neighbours {
+        a: px(-1, -1), b: px(0, -1), c: px(1, -1),
+        d: px(-1, 0), e: px(0, 0), f: px(1, 0),
+        g: px(-1, 1), h: px(0, 1), i: px(1, 1),
+    }
+}
+
+/// Scale2x (AdvMAME2x), `smooth` blends the corners instead of copying the neighbour which gives
+/// a lightweight take on xBR smoothed diagonals.
+fn scale2x(src: &[u8], width: usize, height: usize, smooth: bool, dst: &mut [u8]) {
+    let corner = |e: u32, n: u32| if smooth { blend(e, n) } else { n };
+    for y in 0..height {
+        for x in 0..width {
+            let Neighbours { b, d, e, f, h, .. } = neighbours(src, width, height, x, y);
+            let out = [
+                if d == b && b != f && d != h { corner(e, d) } else { e },
+                if b == f && b != d && f != h { corner(e, f) } else { e },
+                if d == h && d != b && h != f { corner(e, d) } else { e },
+                if h == f && d != h && b != f { corner(e, f) } else { e },
+            ];
+            let o = y * 2 * width * 2 + x * 2;
+            put_pixel(dst, o, out[0]);
+            put_pixel(dst, o + 1, out[1]);
+            put_pixel(dst, o + width * 2, out[2]);
+            put_pixel(dst, o + width * 2 + 1, out[3]);
+        }
+    }
+}
+
+/// Scale3x (AdvMAME3x).
+fn scale3x(src: &[u8], width: usize, height: usize, dst: &mut [u8]) {
+    for y in 0..height {
+        for x in 0..width {
+            let Neighbours { a, b, c, d, e, f, g, h, i } = neighbours(src, width, height, x, y);
+            let db = d == b && b != f && d != h;
+            let bf = b == f && b != d && f != h;
+            let dh = d == h && d != b && h != f;
+            let hf = h == f && d != h && b != f;
+            let out = [
+                if db { d } else { e },
+                if (db && e != c) || (bf && e != a) { b } else { e },
+                if bf { f } else { e },
+                if (db && e != g) || (dh && e != a) { d } else { e },
+                e,
+                if (bf && e != i) || (hf && e != c) { f } else { e },
+                if dh { d } else { e },
+                if (dh && e != i) || (hf && e != g) { h } else { e },
+                if hf { f } else { e },
+            ];
+            for (n, p) in out.iter().enumerate() {
+                put_pixel(dst, (y * 3 + n / 3) * width * 3 + x * 3 + n % 3, *p);
+            }
+        }
+    }
+}
+
+/// Size multiplier of the scaler, 1 when the frame is shown as is and scaled by the GPU.
+pub fn factor(scaler: Scaler) -> usize {
+    match scaler {
+        Scaler::Scale2x | Scaler::Xbr => 2,
+        Scaler::Scale3x => 3,
+        Scaler::Nearest | Scaler::Integer | Scaler::Bilinear => 1,
+    }
+}
+
+/// Upscales an RGBA frame on the CPU into `dst`, reusing its memory.
+pub fn upscale(scaler: Scaler, src: &[u8], width: usize, height: usize, dst: &mut Vec<u8>) {
+    let factor = factor(scaler);
+    dst.clear();
+    dst.resize(src.len() * factor * factor, 0);
+    match scaler {
+        Scaler::Scale2x => scale2x(src, width, height, false, dst),
+        Scaler::Xbr => scale2x(src, width, height, true, dst),
+        Scaler::Scale3x => scale3x(src, width, height, dst),
+        Scaler::Nearest | Scaler::Integer | Scaler::Bilinear => dst.copy_from_slice(src),
+    }
+}
@@ -3,14 +3,23 @@
 use std::fs::OpenOptions;
 use std::path::PathBuf;
 use app::App;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use fd_lock::RwLock;
 use gtk::{gdk, prelude::*};
 use gtk::{glib, Application};
-use runner::runner_config::RunnerConfig;
+use cartridge::{convert::{convert, HeaderOverrides}, lnx_header::LnxHeader, CartInfo};
+use cheats::Cheat;
+use debugger::DebugSnapshot;
+use runner::{profiler::ProfileReport, runner_config::{ColorAdjustments, ColorProfile, OsdElement, Renderer, RunnerConfig, Scaler, SerialBacking, TraceSettings, WatchRestore}, stats::RunnerStats};
 use shared_memory::{ShmemConf, ShmemError};
 
 pub(crate) mod app;
+mod cart_watcher;
+mod cartridge;
+mod cheat_manager;
+mod cheats;
+mod debugger;
+mod library;
 mod sound_source;
 mod lynx_display;
 mod runner;
@@ -34,12 +43,64 @@ struct Args {
     /// Allows only one instance running
     #[arg(short, long, default_value_t = false)]
     single_instance: bool,
+
+    /// Starts in fullscreen
+    #[arg(short, long, default_value_t = false)]
+    fullscreen: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Converts between .lnx images and headerless .lyx/.bin dumps, or rewrites a .lnx header
+    Convert {
+        /// Cartridge to convert
+        input: PathBuf,
+
+        /// Converted file, headerless when its extension is .lyx or .bin
+        output: PathBuf,
+
+        /// Bank 0 size in KiB: 64, 128, 256 or 512
+        #[arg(long)]
+        bank0: Option<u32>,
+
+        /// Bank 1 size in KiB, 0 for none
+        #[arg(long)]
+        bank1: Option<u32>,
+
+        /// Screen rotation
+        #[arg(long, value_parser = ["none", "left", "right"])]
+        rotation: Option<String>,
+
+        /// Cartridge title
+        #[arg(long)]
+        title: Option<String>,
+
+        /// Cartridge manufacturer
+        #[arg(long)]
+        manufacturer: Option<String>,
+    },
 }
 
 pub(crate) enum Event {
-    UpdateDisplay(Vec<u8>),
+    UpdateDisplay(usize),
     UpdateConfig(RunnerConfig),
     LoadCart(PathBuf),
+    LoadCartEntry(PathBuf, String),
+    LoadPatch(PathBuf),
+    LoadHeaderlessCart(PathBuf, Option<String>, LnxHeader),
+    CartridgeInfo,
+    WriteCartHeader(LnxHeader),
+    ImportEeprom(PathBuf),
+    ExportEeprom(PathBuf),
+    WatchCart(bool),
+    WatchRestore(WatchRestore),
+    BookmarkState,
+    CartFileChanged,
+    WatchedCartChanged,
+    AutoPatch(bool),
     LoadROM(PathBuf),
     ReloadCart,
     LoadState(PathBuf),
@@ -49,15 +110,67 @@ pub(crate) enum Event {
     Mute(bool),
     KeyPressed(gdk::Key),
     KeyReleased(gdk::Key),
+    Trace(bool),
+    TraceStopped,
+    TraceSettings,
+    SetTraceSettings(TraceSettings),
+    DebugSnapshot(Box<DebugSnapshot>),
+    DebugSnapshots(bool),
+    RequestSnapshot,
+    SuzyInspector,
+    VideoViewer,
+    AudioInspector,
+    AudioChannels(u8, u8),
+    Profiler,
+    Profile(u32),
+    ProfileReport(Box<ProfileReport>),
+    CartLoaded(u32, PathBuf),
+    CheatManager,
+    Library,
+    ClearRecentCarts,
+    AutoResume(bool),
+    LibraryFolders(Vec<PathBuf>),
+    LibraryScanned(Vec<CartInfo>),
+    RamSearch,
+    AddCheat(Cheat),
+    UpdateCheats(Vec<Cheat>),
+    ComlynxUnits(u8),
+    ButtonsMapping(usize),
+    NetplaySettings(bool),
+    HostNetplay(String),
+    JoinNetplay(String),
+    StopNetplay,
+    NetplayStatus(String),
+    SerialSettings,
+    SerialBacking(SerialBacking),
+    SerialStatus(String),
+    Ghosting(u8),
+    Scaler(Scaler),
+    LcdGrid(bool),
+    WindowScale(u8),
+    Fullscreen(bool),
+    Renderer(Renderer),
+    Shader(Option<String>),
+    RendererFailed(String),
+    ColorSettings,
+    ColorCorrection(ColorProfile, ColorAdjustments),
+    Osd(OsdElement, bool),
+    RunnerStats(RunnerStats),
+    OsdMessage(String),
     About,
     Quit,
 }
 
 fn main() -> glib::ExitCode {  
     env_logger::init(); 
+    let mut args = Args::parse();
+    if let Some(command) = args.command.take() {
+        return run_command(command);
+    }
+
     let mainapp = Application::builder().application_id(APP_ID).build();
     
-    let config = process_args();
+    let config = process_args(args);
 
     let mut file_lock = RwLock::new(
         OpenOptions::new()
@@ -85,9 +198,22 @@ fn main() -> glib::ExitCode {
         let event_handler = async move {
             while let Ok(event) = event_rx.as_async().recv().await {
                 match event {
-                    Event::UpdateDisplay(buffer) => app.setup_next_frame(&buffer),
+                    Event::UpdateDisplay(unit) => app.setup_next_frame(unit),
                     Event::UpdateConfig(config) => app.set_new_config(config),
                     Event::LoadCart(file) => app.load_cart(file),
+                    Event::LoadCartEntry(file, entry) => app.load_cart_entry(file, Some(entry)),
+                    Event::LoadPatch(file) => app.load_patch(file),
+                    Event::LoadHeaderlessCart(file, entry, header) => app.load_headerless_cart(file, entry, header),
+                    Event::AutoPatch(auto_patch) => app.auto_patch(auto_patch),
+                    Event::CartridgeInfo => app.show_cart_info(),
+                    Event::WriteCartHeader(header) => app.write_cart_header(header),
+                    Event::ImportEeprom(file) => app.import_eeprom(file),
+                    Event::ExportEeprom(file) => app.export_eeprom(file),
+                    Event::WatchCart(watch) => app.watch_cart(watch),
+                    Event::WatchRestore(restore) => app.watch_restore(restore),
+                    Event::BookmarkState => app.bookmark_state(),
+                    Event::CartFileChanged => app.cart_file_changed(),
+                    Event::WatchedCartChanged => app.reload_watched_cart(),
                     Event::LoadROM(file) => app.load_rom(file),
                     Event::ReloadCart => app.reload_cart(),
                     Event::LoadState(file) => app.load_state(file),
@@ -96,9 +222,61 @@ fn main() -> glib::ExitCode {
                     Event::Reset => app.reset(),
                     Event::Mute(m) => app.mute(m),
                     Event::About => app.show_about(),
-                    Event::Quit => lapp.quit(),
+                    Event::Quit => {
+                        app.save_window_state();
+                        app.save_thumbnail();
+                        app.shutdown();
+                        lapp.quit();
+                    }
                     Event::KeyPressed(key) => app.key_pressed(key),
                     Event::KeyReleased(key) => app.key_released(key),
+                    Event::Trace(t) => app.trace(t),
+                    Event::TraceStopped => app.trace_stopped(),
+                    Event::TraceSettings => app.show_trace_settings(),
+                    Event::SetTraceSettings(settings) => app.set_trace_settings(settings),
+                    Event::DebugSnapshot(snapshot) => app.debug_snapshot(&snapshot),
+                    Event::DebugSnapshots(d) => app.debug_snapshots(d),
+                    Event::RequestSnapshot => app.request_snapshot(),
+                    Event::SuzyInspector => app.show_suzy_inspector(),
+                    Event::VideoViewer => app.show_video_viewer(),
+                    Event::AudioInspector => app.show_audio_inspector(),
+                    Event::AudioChannels(muted, solo) => app.audio_channels(muted, solo),
+                    Event::Profiler => app.show_profiler(),
+                    Event::Profile(frames) => app.profile(frames),
+                    Event::ProfileReport(report) => app.profile_report(*report),
+                    Event::CartLoaded(hash, cartridge) => app.cart_loaded(hash, cartridge),
+                    Event::CheatManager => app.show_cheat_manager(),
+                    Event::RamSearch => app.show_ram_search(),
+                    Event::AddCheat(cheat) => app.add_cheat(cheat),
+                    Event::UpdateCheats(cheats) => app.update_cheats(cheats),
+                    Event::ComlynxUnits(units) => app.comlynx_units(units),
+                    Event::ButtonsMapping(unit) => app.show_key_mapping(unit),
+                    Event::NetplaySettings(host) => app.show_netplay_settings(host),
+                    Event::HostNetplay(address) => app.host_netplay(address),
+                    Event::JoinNetplay(address) => app.join_netplay(address),
+                    Event::StopNetplay => app.stop_netplay(),
+                    Event::NetplayStatus(status) => app.netplay_status(status),
+                    Event::SerialSettings => app.show_serial_settings(),
+                    Event::SerialBacking(backing) => app.serial_backing(backing),
+                    Event::SerialStatus(status) => app.serial_status(status),
+                    Event::Ghosting(persistence) => app.ghosting(persistence),
+                    Event::Scaler(scaler) => app.scaler(scaler),
+                    Event::LcdGrid(lcd_grid) => app.lcd_grid(lcd_grid),
+                    Event::WindowScale(scale) => app.window_scale(scale),
+                    Event::Fullscreen(fullscreen) => app.fullscreen(fullscreen),
+                    Event::Library => app.show_library(),
+                    Event::ClearRecentCarts => app.clear_recent_carts(),
+                    Event::AutoResume(auto_resume) => app.auto_resume(auto_resume),
+                    Event::LibraryFolders(folders) => app.library_folders(folders),
+                    Event::LibraryScanned(carts) => app.library_scanned(carts),
+                    Event::Renderer(renderer) => app.renderer(renderer),
+                    Event::Shader(shader) => app.shader(shader),
+                    Event::RendererFailed(error) => app.renderer_failed(error),
+                    Event::ColorSettings => app.show_color_settings(),
+                    Event::ColorCorrection(profile, adjustments) => app.color_correction(profile, adjustments),
+                    Event::Osd(element, shown) => app.osd(element, shown),
+                    Event::RunnerStats(stats) => app.runner_stats(stats),
+                    Event::OsdMessage(message) => app.osd_message(message),
                 }
             }
         };
@@ -109,9 +287,22 @@ fn main() -> glib::ExitCode {
     mainapp.run_with_args(&[""])
 }
 
-fn process_args() -> RunnerConfig {
-    let args = Args::parse();
+fn run_command(command: Command) -> glib::ExitCode {
+    match command {
+        Command::Convert { input, output, bank0, bank1, rotation, title, manufacturer } => {
+            let overrides = HeaderOverrides { bank0, bank1, rotation, title, manufacturer };
+            match convert(&input, &output, &overrides) {
+                Ok(()) => glib::ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("Couldn't convert '{}'. {}", input.display(), e);
+                    glib::ExitCode::FAILURE
+                }
+            }
+        }
+    }
+}
 
+fn process_args(args: Args) -> RunnerConfig {
     let mut config = RunnerConfig::default();
     if let Some(rom) = args.rom {
         config.set_rom(rom);
@@ -121,6 +312,7 @@ fn process_args() -> RunnerConfig {
     }
     
     config.set_single_instance(args.single_instance);
+    config.set_fullscreen(args.fullscreen);
 
     config
 }
@@ -0,0 +1,13 @@
+use holani::lynx::Lynx;
+
+pub(crate) const AUDIO_CHANNELS: usize = 4;
+pub(crate) const AUDIO_BASE: u16 = 0xfd20;
+pub(crate) const AUDIO_CHANNEL_SIZE: u16 = 8;
+pub(crate) const AUDIO_OUTPUT: u16 = 2;
+pub(crate) const ATTEN_A: u16 = 0xfd40;
+pub(crate) const MPAN: u16 = 0xfd44;
+pub(crate) const MSTEREO: u16 = 0xfd50;
+
+pub(crate) fn channel_output(lynx: &Lynx, channel: usize) -> i8 {
+    lynx.peek_register(AUDIO_BASE + channel as u16 * AUDIO_CHANNEL_SIZE + AUDIO_OUTPUT) as i8
+}
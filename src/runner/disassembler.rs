@@ -0,0 +1,100 @@
+#[derive(Clone, Copy)]
+enum Mode {
+    Imp,
+    Acc,
+    Imm,
+    Zp,
+    Zpx,
+    Zpy,
+    Zpi,
+    Izx,
+    Izy,
+    Abs,
+    Abx,
+    Aby,
+    Ind,
+    Iax,
+    Rel,
+}
+
+use Mode::*;
+
+// 65SC02 as found in the Lynx, the Rockwell bit instructions are single byte NOPs.
+const OPCODES: [(&str, Mode); 256] = [
+    ("BRK", Imp), ("ORA", Izx), ("NOP", Imm), ("NOP", Imp), ("TSB", Zp),  ("ORA", Zp),  ("ASL", Zp),  ("NOP", Imp),
+    ("PHP", Imp), ("ORA", Imm), ("ASL", Acc), ("NOP", Imp), ("TSB", Abs), ("ORA", Abs), ("ASL", Abs), ("NOP", Imp),
+    ("BPL", Rel), ("ORA", Izy), ("ORA", Zpi), ("NOP", Imp), ("TRB", Zp),  ("ORA", Zpx), ("ASL", Zpx), ("NOP", Imp),
+    ("CLC", Imp), ("ORA", Aby), ("INC", Acc), ("NOP", Imp), ("TRB", Abs), ("ORA", Abx), ("ASL", Abx), ("NOP", Imp),
+    ("JSR", Abs), ("AND", Izx), ("NOP", Imm), ("NOP", Imp), ("BIT", Zp),  ("AND", Zp),  ("ROL", Zp),  ("NOP", Imp),
+    ("PLP", Imp), ("AND", Imm), ("ROL", Acc), ("NOP", Imp), ("BIT", Abs), ("AND", Abs), ("ROL", Abs), ("NOP", Imp),
+    ("BMI", Rel), ("AND", Izy), ("AND", Zpi), ("NOP", Imp), ("BIT", Zpx), ("AND", Zpx), ("ROL", Zpx), ("NOP", Imp),
+    ("SEC", Imp), ("AND", Aby), ("DEC", Acc), ("NOP", Imp), ("BIT", Abx), ("AND", Abx), ("ROL", Abx), ("NOP", Imp),
+    ("RTI", Imp), ("EOR", Izx), ("NOP", Imm), ("NOP", Imp), ("NOP", Zp),  ("EOR", Zp),  ("LSR", Zp),  ("NOP", Imp),
+    ("PHA", Imp), ("EOR", Imm), ("LSR", Acc), ("NOP", Imp), ("JMP", Abs), ("EOR", Abs), ("LSR", Abs), ("NOP", Imp),
+    ("BVC", Rel), ("EOR", Izy), ("EOR", Zpi), ("NOP", Imp), ("NOP", Zpx), ("EOR", Zpx), ("LSR", Zpx), ("NOP", Imp),
+    ("CLI", Imp), ("EOR", Aby), ("PHY", Imp), ("NOP", Imp), ("NOP", Abs), ("EOR", Abx), ("LSR", Abx), ("NOP", Imp),
+    ("RTS", Imp), ("ADC", Izx), ("NOP", Imm), ("NOP", Imp), ("STZ", Zp),  ("ADC", Zp),  ("ROR", Zp),  ("NOP", Imp),
+    ("PLA", Imp), ("ADC", Imm), ("ROR", Acc), ("NOP", Imp), ("JMP", Ind), ("ADC", Abs), ("ROR", Abs), ("NOP", Imp),
+    ("BVS", Rel), ("ADC", Izy), ("ADC", Zpi), ("NOP", Imp), ("STZ", Zpx), ("ADC", Zpx), ("ROR", Zpx), ("NOP", Imp),
+    ("SEI", Imp), ("ADC", Aby), ("PLY", Imp), ("NOP", Imp), ("JMP", Iax), ("ADC", Abx), ("ROR", Abx), ("NOP", Imp),
+    ("BRA", Rel), ("STA", Izx), ("NOP", Imm), ("NOP", Imp), ("STY", Zp),  ("STA", Zp),  ("STX", Zp),  ("NOP", Imp),
+    ("DEY", Imp), ("BIT", Imm), ("TXA", Imp), ("NOP", Imp), ("STY", Abs), ("STA", Abs), ("STX", Abs), ("NOP", Imp),
+    ("BCC", Rel), ("STA", Izy), ("STA", Zpi), ("NOP", Imp), ("STY", Zpx), ("STA", Zpx), ("STX", Zpy), ("NOP", Imp),
+    ("TYA", Imp), ("STA", Aby), ("TXS", Imp), ("NOP", Imp), ("STZ", Abs), ("STA", Abx), ("STZ", Abx), ("NOP", Imp),
+    ("LDY", Imm), ("LDA", Izx), ("LDX", Imm), ("NOP", Imp), ("LDY", Zp),  ("LDA", Zp),  ("LDX", Zp),  ("NOP", Imp),
+    ("TAY", Imp), ("LDA", Imm), ("TAX", Imp), ("NOP", Imp), ("LDY", Abs), ("LDA", Abs), ("LDX", Abs), ("NOP", Imp),
+    ("BCS", Rel), ("LDA", Izy), ("LDA", Zpi), ("NOP", Imp), ("LDY", Zpx), ("LDA", Zpx), ("LDX", Zpy), ("NOP", Imp),
+    ("CLV", Imp), ("LDA", Aby), ("TSX", Imp), ("NOP", Imp), ("LDY", Abx), ("LDA", Abx), ("LDX", Aby), ("NOP", Imp),
+    ("CPY", Imm), ("CMP", Izx), ("NOP", Imm), ("NOP", Imp), ("CPY", Zp),  ("CMP", Zp),  ("DEC", Zp),  ("NOP", Imp),
+    ("INY", Imp), ("CMP", Imm), ("DEX", Imp), ("NOP", Imp), ("CPY", Abs), ("CMP", Abs), ("DEC", Abs), ("NOP", Imp),
+    ("BNE", Rel), ("CMP", Izy), ("CMP", Zpi), ("NOP", Imp), ("NOP", Zpx), ("CMP", Zpx), ("DEC", Zpx), ("NOP", Imp),
+    ("CLD", Imp), ("CMP", Aby), ("PHX", Imp), ("NOP", Imp), ("NOP", Abs), ("CMP", Abx), ("DEC", Abx), ("NOP", Imp),
+    ("CPX", Imm), ("SBC", Izx), ("NOP", Imm), ("NOP", Imp), ("CPX", Zp),  ("SBC", Zp),  ("INC", Zp),  ("NOP", Imp),
+    ("INX", Imp), ("SBC", Imm), ("NOP", Imp), ("NOP", Imp), ("CPX", Abs), ("SBC", Abs), ("INC", Abs), ("NOP", Imp),
+    ("BEQ", Rel), ("SBC", Izy), ("SBC", Zpi), ("NOP", Imp), ("NOP", Zpx), ("SBC", Zpx), ("INC", Zpx), ("NOP", Imp),
+    ("SED", Imp), ("SBC", Aby), ("PLX", Imp), ("NOP", Imp), ("NOP", Abs), ("SBC", Abx), ("INC", Abx), ("NOP", Imp),
+];
+
+pub(crate) struct Instruction {
+    pub(crate) bytes: Vec<u8>,
+    pub(crate) text: String,
+}
+
+/// Decodes the instruction located at `pc`, `read` gives access to the CPU address space.
+pub(crate) fn disassemble(pc: u16, read: impl Fn(u16) -> u8) -> Instruction {
+    let opcode = read(pc);
+    let (mnemonic, mode) = OPCODES[opcode as usize];
+
+    let len: u16 = match mode {
+        Imp | Acc => 1,
+        Imm | Zp | Zpx | Zpy | Zpi | Izx | Izy | Rel => 2,
+        Abs | Abx | Aby | Ind | Iax => 3,
+    };
+
+    let bytes: Vec<u8> = (0..len).map(|i| read(pc.wrapping_add(i))).collect();
+    let byte = bytes.get(1).copied().unwrap_or(0);
+    let word = u16::from_le_bytes([byte, bytes.get(2).copied().unwrap_or(0)]);
+
+    let operand = match mode {
+        Imp => String::new(),
+        Acc => " A".to_string(),
+        Imm => format!(" #${:02X}", byte),
+        Zp => format!(" ${:02X}", byte),
+        Zpx => format!(" ${:02X},X", byte),
+        Zpy => format!(" ${:02X},Y", byte),
+        Zpi => format!(" (${:02X})", byte),
+        Izx => format!(" (${:02X},X)", byte),
+        Izy => format!(" (${:02X}),Y", byte),
+        Abs => format!(" ${:04X}", word),
+        Abx => format!(" ${:04X},X", word),
+        Aby => format!(" ${:04X},Y", word),
+        Ind => format!(" (${:04X})", word),
+        Iax => format!(" (${:04X},X)", word),
+        Rel => format!(" ${:04X}", pc.wrapping_add(2).wrapping_add(byte as i8 as u16)),
+    };
+
+    Instruction {
+        bytes,
+        text: format!("{}{}", mnemonic, operand),
+    }
+}
@@ -0,0 +1,69 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use holani::lynx::Lynx;
+use log::error;
+
+use super::runner_config::{cart_file_name, data_file};
+
+/// How often a changed EEPROM is written to disk.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Per cartridge EEPROM save, keyed by the cart content hash so renamed files keep their saves.
+fn eeprom_file(cart_hash: u32) -> Option<PathBuf> {
+    data_file("eeprom", &cart_file_name(cart_hash, "eep"))
+}
+
+/// Copies `data` into the cart EEPROM, returns false when the cart has none.
+pub(crate) fn import_eeprom(lynx: &mut Lynx, data: &[u8]) -> bool {
+    let Some(eeprom) = lynx.eeprom_data_mut() else {
+        return false;
+    };
+    let len = eeprom.len().min(data.len());
+    eeprom[..len].copy_from_slice(&data[..len]);
+    true
+}
+
+/// Keeps the serial EEPROM of the running cart in its `.eep` file.
+pub(crate) struct EepromStore {
+    file: PathBuf,
+    saved: Vec<u8>,
+    next_flush: Instant,
+}
+
+impl EepromStore {
+    /// Restores the saved EEPROM of the loaded cart, `None` when the cart has no EEPROM.
+    pub(crate) fn open(lynx: &mut Lynx, cart_hash: u32) -> Option<Self> {
+        lynx.eeprom_data()?;
+        let file = eeprom_file(cart_hash)?;
+        if let Ok(data) = std::fs::read(&file) {
+            import_eeprom(lynx, &data);
+        }
+        Some(Self {
+            file,
+            saved: lynx.eeprom_data()?.to_vec(),
+            next_flush: Instant::now() + FLUSH_INTERVAL,
+        })
+    }
+
+    /// Writes the EEPROM when it changed since the last write, at most every `FLUSH_INTERVAL`
+    /// unless `force` is set.
+    pub(crate) fn flush(&mut self, lynx: &Lynx, force: bool) {
+        if !force && Instant::now() < self.next_flush {
+            return;
+        }
+        self.next_flush = Instant::now() + FLUSH_INTERVAL;
+        let Some(data) = lynx.eeprom_data() else {
+            return;
+        };
+        if data == self.saved.as_slice() {
+            return;
+        }
+        match std::fs::write(&self.file, data) {
+            Ok(()) => self.saved = data.to_vec(),
+            Err(e) => error!("Couldn't save EEPROM to '{}'. '{}'", self.file.display(), e),
+        }
+    }
+}
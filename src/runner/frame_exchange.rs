@@ -0,0 +1,61 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+
+/// Middle buffer of a triple buffer, the runner and the UI each own one of the two others and swap
+/// it with this one. A frame the UI didn't pick up in time is overwritten by the next one.
+#[derive(Default)]
+struct Exchange {
+    middle: Mutex<(Vec<u8>, bool)>,
+    /// An `UpdateDisplay` event is on its way to the UI.
+    notified: AtomicBool,
+}
+
+/// Runner end of a unit's frame exchange.
+pub(crate) struct FrameWriter {
+    exchange: Arc<Exchange>,
+    back: Vec<u8>,
+}
+
+/// UI end of a unit's frame exchange.
+pub(crate) struct FrameReader {
+    exchange: Arc<Exchange>,
+    front: Vec<u8>,
+}
+
+pub(crate) fn frame_exchange() -> (FrameWriter, FrameReader) {
+    let exchange = Arc::new(Exchange::default());
+    (
+        FrameWriter { exchange: exchange.clone(), back: vec![] },
+        FrameReader { exchange, front: vec![] },
+    )
+}
+
+impl FrameWriter {
+    /// Publishes a frame, returns true when the UI has to be notified.
+    pub(crate) fn publish(&mut self, data: &[u8]) -> bool {
+        self.back.clear();
+        self.back.extend_from_slice(data);
+        {
+            let mut middle = self.exchange.middle.lock().unwrap();
+            std::mem::swap(&mut self.back, &mut middle.0);
+            middle.1 = true;
+        }
+        !self.exchange.notified.swap(true, Ordering::AcqRel)
+    }
+}
+
+impl FrameReader {
+    /// Latest published frame, `None` if it has already been taken.
+    pub(crate) fn take(&mut self) -> Option<&[u8]> {
+        self.exchange.notified.store(false, Ordering::Release);
+        let mut middle = self.exchange.middle.lock().unwrap();
+        if !middle.1 {
+            return None;
+        }
+        std::mem::swap(&mut self.front, &mut middle.0);
+        middle.1 = false;
+        Some(&self.front)
+    }
+}
@@ -1,14 +1,25 @@
 use std::thread::JoinHandle;
 use holani::cartridge::lnx_header::LNXRotation;
-use log::trace;
+use log::{error, trace};
 use perframe_runner_thread::PerFrameRunnerThread;
-use runner_config::RunnerConfig;
+use frame_exchange::{frame_exchange, FrameReader};
+use runner_config::{RunnerConfig, MAX_COMLYNX_UNITS};
 use thread_priority::*;
 
 use crate::Event;
 
 pub(crate) mod runner_config;
 pub(crate) mod perframe_runner_thread;
+pub(crate) mod disassembler;
+pub(crate) mod trace_logger;
+pub(crate) mod audio_channels;
+pub(crate) mod profiler;
+pub(crate) mod netplay;
+pub(crate) mod frame_exchange;
+pub(crate) mod stats;
+pub(crate) mod eeprom_store;
+#[cfg(unix)]
+pub(crate) mod serial_link;
 
 pub const CRYSTAL_FREQUENCY: u32 = 16_000_000;
 pub const SAMPLE_RATE: u32 = 16_000;
@@ -29,18 +40,20 @@ impl Runner {
         }
     }
 
-    pub fn initialize_thread(&mut self, event_tx: kanal::Sender<Event>, config: RunnerConfig) -> (kanal::Sender<(u8, u8)>, kanal::Sender<RunnerConfig>, LNXRotation) {
-        let (input_tx, input_rx) = kanal::unbounded::<(u8, u8)>();
+    pub fn initialize_thread(&mut self, event_tx: kanal::Sender<Event>, config: RunnerConfig) -> (kanal::Sender<(usize, u8, u8)>, kanal::Sender<RunnerConfig>, Vec<FrameReader>, LNXRotation) {
+        let (input_tx, input_rx) = kanal::unbounded::<(usize, u8, u8)>();
         let (config_tx, config_rx) = kanal::unbounded::<RunnerConfig>();
         let (rotation_tx, rotation_rx) = kanal::unbounded::<LNXRotation>();
 
+        let (frame_writers, frame_readers): (Vec<_>, Vec<_>) = (0..MAX_COMLYNX_UNITS).map(|_| frame_exchange()).unzip();
+
         let conf = config.clone();
 
         self.runner_thread = Some(
             std::thread::Builder::new()
             .name("Core".to_string())
             .spawn_with_priority(ThreadPriority::Max, move |_| {
-                let mut thread: Box<dyn RunnerThread> = Box::new(PerFrameRunnerThread::new(conf, input_rx, config_rx, event_tx, rotation_tx));
+                let mut thread: Box<dyn RunnerThread> = Box::new(PerFrameRunnerThread::new(conf, input_rx, config_rx, event_tx, rotation_tx, frame_writers));
                 trace!("Runner started.");
                 thread.initialize().unwrap_or_else(|err| {
                     println!("Error: {}", err);
@@ -53,6 +66,15 @@ impl Runner {
 
         let rotation = rotation_rx.recv().unwrap();
        
-        (input_tx, config_tx, rotation)
+        (input_tx, config_tx, frame_readers, rotation)
+    }
+
+    /// Waits for the runner thread to end, after a `RunnerAction::Shutdown`.
+    pub fn join(&mut self) {
+        if let Some(thread) = self.runner_thread.take() {
+            if thread.join().is_err() {
+                error!("Runner thread panicked.");
+            }
+        }
     }
 }
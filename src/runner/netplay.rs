@@ -0,0 +1,284 @@
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+};
+use log::trace;
+
+pub(crate) const DEFAULT_NETPLAY_PORT: u16 = 6502;
+
+/// Frames between a local input and the frame it is applied to, hides the network latency.
+const INPUT_DELAY: u32 = 4;
+/// Frames the peers can be apart. The lockstep keeps them within `INPUT_DELAY`, messages about
+/// frames further away are dropped or rejected so the tables stay small.
+const FRAME_WINDOW: u32 = INPUT_DELAY * 4;
+/// Frames between two state hashes comparisons.
+const HASH_INTERVAL: u32 = 60;
+const MESSAGE_SIZE: usize = 9;
+/// Sent in the HELLO frame field, peers with a different protocol refuse the connection.
+const PROTOCOL_VERSION: u32 = 1;
+
+const HELLO: u8 = 0;
+const INPUT: u8 = 1;
+const HASH: u8 = 2;
+
+#[derive(PartialEq)]
+enum State {
+    Connecting,
+    Handshake,
+    Started,
+}
+
+/// Two instances linked over TCP. Instead of streaming the UART bytes, which would need both cores
+/// to agree on every cycle, each peer emulates both Lynx units of the ComLynx cable and only the
+/// inputs are exchanged. A frame is emulated once both inputs for it are known, which keeps the
+/// cores in lockstep, and the state hashes are periodically compared to detect desyncs.
+pub(crate) struct Netplay {
+    /// Host side, polled for the peer connection and closed once dropped.
+    listener: Option<TcpListener>,
+    /// Joining side, the connection is made on a separate thread.
+    pending: Option<kanal::Receiver<io::Result<TcpStream>>>,
+    stream: Option<TcpStream>,
+    state: State,
+    buffer: Vec<u8>,
+    /// Messages not written yet, the socket being non-blocking.
+    outgoing: Vec<u8>,
+    local_unit: usize,
+    cart_hash: u32,
+    frame: u32,
+    sent_frame: Option<u32>,
+    local_inputs: HashMap<u32, (u8, u8)>,
+    remote_inputs: HashMap<u32, (u8, u8)>,
+    local_hashes: HashMap<u32, u32>,
+    remote_hashes: HashMap<u32, u32>,
+}
+
+fn message(tag: u8, frame: u32, value: u32) -> [u8; MESSAGE_SIZE] {
+    let mut msg = [0; MESSAGE_SIZE];
+    msg[0] = tag;
+    msg[1..5].copy_from_slice(&frame.to_le_bytes());
+    msg[5..9].copy_from_slice(&value.to_le_bytes());
+    msg
+}
+
+impl Netplay {
+    fn new(local_unit: usize, cart_hash: u32) -> Self {
+        Self {
+            listener: None,
+            pending: None,
+            stream: None,
+            state: State::Connecting,
+            buffer: vec![],
+            outgoing: vec![],
+            local_unit,
+            cart_hash,
+            frame: 0,
+            sent_frame: None,
+            local_inputs: HashMap::new(),
+            remote_inputs: HashMap::new(),
+            local_hashes: HashMap::new(),
+            remote_hashes: HashMap::new(),
+        }
+    }
+
+    /// Waits for a peer on the `host:port` address, the host plays the first unit.
+    pub(crate) fn host(address: String, cart_hash: u32) -> io::Result<Self> {
+        let listener = TcpListener::bind(address.as_str())?;
+        listener.set_nonblocking(true)?;
+        trace!("Netplay waiting on {}.", address);
+        let mut netplay = Self::new(0, cart_hash);
+        netplay.listener = Some(listener);
+        Ok(netplay)
+    }
+
+    /// Connects to a host at `address`, the joining peer plays the second unit.
+    pub(crate) fn join(address: String, cart_hash: u32) -> io::Result<Self> {
+        let (tx, rx) = kanal::bounded(1);
+        std::thread::spawn(move || {
+            let _ = tx.send(TcpStream::connect(address));
+        });
+        let mut netplay = Self::new(1, cart_hash);
+        netplay.pending = Some(rx);
+        Ok(netplay)
+    }
+
+    /// Unit driven by the local inputs.
+    pub(crate) fn local_unit(&self) -> usize {
+        self.local_unit
+    }
+
+    pub(crate) fn started(&self) -> bool {
+        self.state == State::Started
+    }
+
+    /// Polls the connection, returns true once, when both peers are ready to start from frame 0.
+    pub(crate) fn poll(&mut self) -> io::Result<bool> {
+        if let Some(listener) = self.listener.as_ref() {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    self.listener = None;
+                    self.connected(stream)?;
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(false),
+                Err(e) => return Err(e),
+            }
+        }
+        if let Some(pending) = self.pending.as_ref() {
+            match pending.try_recv() {
+                Ok(None) => return Ok(false),
+                Ok(Some(stream)) => {
+                    self.pending = None;
+                    self.connected(stream?)?;
+                }
+                Err(_) => return Err(io::Error::new(io::ErrorKind::NotConnected, "Connection aborted")),
+            }
+        }
+        let handshake = self.state == State::Handshake;
+        self.flush()?;
+        self.receive()?;
+        Ok(handshake && self.started())
+    }
+
+    fn connected(&mut self, stream: TcpStream) -> io::Result<()> {
+        stream.set_nodelay(true)?;
+        stream.set_nonblocking(true)?;
+        self.stream = Some(stream);
+        self.state = State::Handshake;
+        self.send(HELLO, PROTOCOL_VERSION, self.cart_hash);
+        self.flush()
+    }
+
+    /// Queues a message, it's written by the next `flush`.
+    fn send(&mut self, tag: u8, frame: u32, value: u32) {
+        self.outgoing.extend_from_slice(&message(tag, frame, value));
+    }
+
+    /// Writes the queued messages the socket accepts without blocking.
+    fn flush(&mut self) -> io::Result<()> {
+        let Some(stream) = self.stream.as_mut() else {
+            return Ok(());
+        };
+        while !self.outgoing.is_empty() {
+            match stream.write(&self.outgoing) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => {
+                    self.outgoing.drain(..n);
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Rejects a frame sent by the peer further ahead than the lockstep allows.
+    fn check_window(&self, frame: u32) -> io::Result<()> {
+        match frame > self.frame + FRAME_WINDOW {
+            true => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Peer sent frame {} at frame {}", frame, self.frame))),
+            false => Ok(()),
+        }
+    }
+
+    fn receive(&mut self) -> io::Result<()> {
+        let Some(stream) = self.stream.as_mut() else {
+            return Ok(());
+        };
+        let mut data = [0; 512];
+        loop {
+            match stream.read(&mut data) {
+                Ok(0) => return Err(io::Error::new(io::ErrorKind::ConnectionAborted, "Peer disconnected")),
+                Ok(n) => self.buffer.extend_from_slice(&data[..n]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        while self.buffer.len() >= MESSAGE_SIZE {
+            let msg: Vec<u8> = self.buffer.drain(..MESSAGE_SIZE).collect();
+            let frame = u32::from_le_bytes([msg[1], msg[2], msg[3], msg[4]]);
+            let value = u32::from_le_bytes([msg[5], msg[6], msg[7], msg[8]]);
+            match msg[0] {
+                HELLO if frame != PROTOCOL_VERSION => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "The peer runs an incompatible holani-gtk version"));
+                }
+                HELLO if value != self.cart_hash => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Both peers need to run the same cartridge"));
+                }
+                HELLO => self.state = State::Started,
+                INPUT => {
+                    self.check_window(frame)?;
+                    if frame >= self.frame {
+                        self.remote_inputs.insert(frame, (value as u8, (value >> 8) as u8));
+                    }
+                }
+                HASH => {
+                    self.check_window(frame)?;
+                    if frame + FRAME_WINDOW >= self.frame {
+                        self.remote_hashes.insert(frame, value);
+                    }
+                }
+                tag => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Unknown message {}", tag))),
+            }
+        }
+        Ok(())
+    }
+
+    /// Inputs of both units for the current frame, `None` while the peer's ones haven't arrived yet.
+    pub(crate) fn frame_inputs(&mut self, local: (u8, u8)) -> io::Result<Option<[(u8, u8); 2]>> {
+        if !self.started() {
+            return Ok(None);
+        }
+        if self.sent_frame != Some(self.frame) {
+            self.sent_frame = Some(self.frame);
+            self.local_inputs.insert(self.frame + INPUT_DELAY, local);
+            self.send(INPUT, self.frame + INPUT_DELAY, local.0 as u32 | (local.1 as u32) << 8);
+            self.flush()?;
+        }
+        self.receive()?;
+
+        let (local, remote) = match self.frame < INPUT_DELAY {
+            true => ((0, 0), (0, 0)),
+            false => match (self.local_inputs.get(&self.frame), self.remote_inputs.get(&self.frame)) {
+                (Some(local), Some(remote)) => (*local, *remote),
+                _ => return Ok(None),
+            },
+        };
+        Ok(Some(match self.local_unit {
+            0 => [local, remote],
+            _ => [remote, local],
+        }))
+    }
+
+    /// Ends the current frame, `hash` is only called when the state has to be compared.
+    /// Returns the frame number of a detected desync.
+    pub(crate) fn end_frame(&mut self, hash: impl FnOnce() -> u32) -> io::Result<Option<u32>> {
+        self.local_inputs.remove(&self.frame);
+        self.remote_inputs.remove(&self.frame);
+        self.frame += 1;
+
+        if self.frame % HASH_INTERVAL == 0 {
+            let hash = hash();
+            self.local_hashes.insert(self.frame, hash);
+            self.send(HASH, self.frame, hash);
+            self.flush()?;
+        }
+
+        let compared: Vec<u32> = self
+            .local_hashes
+            .keys()
+            .filter(|frame| self.remote_hashes.contains_key(frame))
+            .copied()
+            .collect();
+        let mut desync = None;
+        for frame in compared {
+            if self.local_hashes.remove(&frame) != self.remote_hashes.remove(&frame) {
+                desync = Some(desync.map_or(frame, |d: u32| d.min(frame)));
+            }
+        }
+        // Hashes the peer never answered.
+        let oldest = self.frame.saturating_sub(FRAME_WINDOW);
+        self.local_hashes.retain(|frame, _| *frame >= oldest);
+        self.remote_hashes.retain(|frame, _| *frame >= oldest);
+        Ok(desync)
+    }
+}
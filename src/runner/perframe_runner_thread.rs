@@ -1,17 +1,32 @@
 use super::{
-    runner_config::{RunnerAction, RunnerStatus},
+    audio_channels::{self, AUDIO_CHANNELS},
+    eeprom_store::{import_eeprom, EepromStore},
+    frame_exchange::FrameWriter,
+    netplay::Netplay,
+    profiler::Profiler,
+    runner_config::{RunnerAction, RunnerStatus, SerialBacking},
+    stats::{FrameCounter, RunnerStats},
+    trace_logger::TraceLogger,
     RunnerConfig, RunnerThread, CRYSTAL_FREQUENCY, SAMPLE_RATE,
 };
-use crate::{sound_source::SoundSource, Event, CART_ID, LOCK_SIZE};
+#[cfg(unix)]
+use super::serial_link::SerialLink;
+use crate::{
+    cartridge::{archive::read_cart_file, cart_hash, is_headerless, lnx_header::LnxHeader, patch::{apply_patch, find_patch}},
+    debugger::{DebugSnapshot, SCBNEXT, SPRSYS},
+    sound_source::SoundSource,
+    Event, CART_ID, LOCK_SIZE,
+};
 use holani::{cartridge::lnx_header::LNXRotation, lynx::Lynx};
-use log::trace;
+use log::{error, trace};
 use ringbuf::{
-    traits::{Producer as _, Split as _},
+    traits::{Observer as _, Producer as _, Split as _},
     HeapProd, HeapRb,
 };
 use rodio::OutputStream;
 use shared_memory::{Shmem, ShmemConf, ShmemError};
 use std::{
+    io,
     path::{Path, PathBuf},
     str::FromStr,
     time::{Duration, Instant},
@@ -20,40 +35,92 @@ use std::{
 const TICKS_PER_AUDIO_SAMPLE: u64 = CRYSTAL_FREQUENCY as u64 / SAMPLE_RATE as u64;
 const SAMPLE_BUFFER_SIZE: usize = 2048;
 
+/// Cartridge data as handed to the Lynx.
+struct CartImage {
+    data: Vec<u8>,
+    /// Tells what was patched.
+    patch_message: Option<String>,
+    /// `cart_hash` of the file content, before patching.
+    hash: u32,
+}
+
 pub(crate) struct PerFrameRunnerThread {
     lynx: Lynx,
+    guests: Vec<Lynx>,
     sound_tick: u64,
     config: RunnerConfig,
-    input_rx: kanal::Receiver<(u8, u8)>,
+    input_rx: kanal::Receiver<(usize, u8, u8)>,
     config_rx: kanal::Receiver<RunnerConfig>,
     event_tx: kanal::Sender<Event>,
     rotation_tx: kanal::Sender<LNXRotation>,
+    frame_writers: Vec<FrameWriter>,
     frame_time: Duration,
     next_lcd_refresh: Instant,
     last_refresh_rate: f64,
     stream: Option<OutputStream>,
+    tracer: Option<TraceLogger>,
+    snapshot_requested: bool,
+    sprite_busy: bool,
+    frame_sprite_chains: Vec<u16>,
+    sprite_chains: Vec<u16>,
+    frame_audio: [Vec<i8>; AUDIO_CHANNELS],
+    /// Channel mask last given to Mikey, `None` when a reset or a loaded state may have changed it.
+    enabled_channels: Option<u8>,
+    profiler: Option<Profiler>,
+    cart_hash: Option<u32>,
+    /// Hash of the data actually loaded, patches and header included, netplay peers compare it.
+    image_hash: Option<u32>,
+    netplay: Option<Netplay>,
+    local_input: (u8, u8),
+    serial_backing: SerialBacking,
+    #[cfg(unix)]
+    serial: Option<SerialLink>,
+    frame_counter: FrameCounter,
+    eeprom: Option<EepromStore>,
+    shutdown: bool,
 }
 
 impl PerFrameRunnerThread {
     pub(crate) fn new(
         config: RunnerConfig,
-        input_rx: kanal::Receiver<(u8, u8)>,
+        input_rx: kanal::Receiver<(usize, u8, u8)>,
         config_rx: kanal::Receiver<RunnerConfig>,
         event_tx: kanal::Sender<Event>,
         rotation_tx: kanal::Sender<LNXRotation>,
+        frame_writers: Vec<FrameWriter>,
     ) -> Self {
         Self {
             lynx: Lynx::new(),
+            guests: vec![],
             config,
             input_rx,
             config_rx,
             event_tx,
             rotation_tx,
+            frame_writers,
             sound_tick: 0,
             frame_time: Duration::from_millis(16),
             last_refresh_rate: 0f64,
             next_lcd_refresh: Instant::now(),
             stream: None,
+            tracer: None,
+            snapshot_requested: false,
+            sprite_busy: false,
+            frame_sprite_chains: vec![],
+            sprite_chains: vec![],
+            frame_audio: Default::default(),
+            enabled_channels: None,
+            profiler: None,
+            cart_hash: None,
+            image_hash: None,
+            netplay: None,
+            local_input: (0, 0),
+            serial_backing: SerialBacking::Off,
+            #[cfg(unix)]
+            serial: None,
+            frame_counter: FrameCounter::new(),
+            eeprom: None,
+            shutdown: false,
         }
     }
 
@@ -69,24 +136,165 @@ impl PerFrameRunnerThread {
         }
 
         self.sound_tick = 0;
+
+        if self.capturing() {
+            for (channel, samples) in self.frame_audio.iter_mut().enumerate() {
+                samples.push(audio_channels::channel_output(&self.lynx, channel));
+            }
+        }
+
         let (l, r) = self.lynx.audio_sample();
         sound_buffer.push_slice(&[l, r]);
     }
 
     fn display(&mut self) {
         trace!("Display updated.");
-        let _ = self
-            .event_tx
-            .try_send(Event::UpdateDisplay(self.lynx.screen_rgba().to_vec()))
-            .is_ok();
+        let units = std::iter::once(&self.lynx).chain(self.guests.iter());
+        for (unit, (writer, lynx)) in self.frame_writers.iter_mut().zip(units).enumerate() {
+            if writer.publish(lynx.screen_rgba()) {
+                let _ = self.event_tx.try_send(Event::UpdateDisplay(unit));
+            }
+        }
+    }
+
+    /// Creates a Lynx unit running the same ROM and cart, plugged on the main unit ComLynx cable.
+    fn new_guest(&self) -> Lynx {
+        let mut guest = Lynx::new();
+        if let Some(data) = self.rom_data() {
+            let _ = guest.load_rom_from_slice(&data);
+        }
+        if let Some(image) = self.cart_image() {
+            let _ = guest.load_cart_from_slice(&image.data);
+        }
+        guest.set_comlynx_cable(&self.lynx.comlynx_cable().clone());
+        guest.reset();
+        guest
+    }
+
+    fn guest_count(&self) -> usize {
+        match self.config.cartridge() {
+            None => 0,
+            Some(_) => self.config.units() - 1,
+        }
+    }
+
+    fn sync_guests(&mut self) {
+        let count = self.guest_count();
+        self.guests.truncate(count);
+        while self.guests.len() < count {
+            let guest = self.new_guest();
+            self.guests.push(guest);
+        }
+    }
+
+    fn restart_guests(&mut self) {
+        self.guests.clear();
+        self.sync_guests();
+    }
+
+    fn trace(&mut self) {
+        let Some(tracer) = self.tracer.as_mut() else {
+            return;
+        };
+        if let Err(e) = tracer.tick(&self.lynx) {
+            error!("Couldn't write trace. '{}'", e);
+            self.stop_trace();
+        }
+    }
+
+    fn trace_frame(&mut self) {
+        if self.tracer.as_mut().is_some_and(|tracer| tracer.frame()) {
+            trace!("Trace frame limit reached.");
+            self.stop_trace();
+        }
+    }
+
+    fn stop_trace(&mut self) {
+        self.tracer = None;
+        self.config.set_trace(false);
+        let _ = self.event_tx.try_send(Event::TraceStopped);
+    }
+
+    fn profile(&mut self) {
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.tick(&self.lynx);
+        }
+    }
+
+    fn profile_frame(&mut self) {
+        if self.config.status() != RunnerStatus::Running {
+            return;
+        }
+        if self.profiler.as_mut().is_some_and(|profiler| profiler.frame()) {
+            trace!("Profile capture done.");
+            let report = self.profiler.take().unwrap().report();
+            let _ = self.event_tx.try_send(Event::ProfileReport(Box::new(report)));
+        }
+    }
+
+    fn cheats(&mut self) {
+        for cheat in self.config.cheats().iter().filter(|c| c.enabled) {
+            let ram = self.lynx.ram_data_mut();
+            let addr = cheat.address as usize;
+            if cheat.compare.is_none_or(|compare| ram[addr] == compare) {
+                ram[addr] = cheat.value;
+            }
+        }
+    }
+
+    fn capturing(&self) -> bool {
+        self.snapshot_requested || self.config.debug_snapshots()
+    }
+
+    /// Records the SCB chains submitted to Suzy during the frame.
+    fn sprites(&mut self) {
+        if !self.capturing() {
+            return;
+        }
+        let busy = self.lynx.peek_register(SPRSYS) & 0x01 != 0;
+        if busy && !self.sprite_busy {
+            let scb = u16::from_le_bytes([self.lynx.peek_register(SCBNEXT), self.lynx.peek_register(SCBNEXT + 1)]);
+            self.frame_sprite_chains.push(scb);
+        }
+        self.sprite_busy = busy;
+    }
+
+    fn snapshot(&mut self) {
+        if !self.capturing() {
+            return;
+        }
+        if !self.frame_sprite_chains.is_empty() {
+            self.sprite_chains = std::mem::take(&mut self.frame_sprite_chains);
+        }
+        self.snapshot_requested = false;
+        let snapshot = DebugSnapshot::capture(
+            &self.lynx,
+            self.sprite_chains.clone(),
+            std::mem::take(&mut self.frame_audio),
+        );
+        let _ = self.event_tx.try_send(Event::DebugSnapshot(Box::new(snapshot)));
     }
 
     fn inputs(&mut self) -> bool {
         if self.input_rx.is_disconnected() {
             return true;
-        } else if let Ok(Some((joy, sw))) = self.input_rx.try_recv() {
-            self.lynx.set_joystick_u8(joy);
-            self.lynx.set_switches_u8(sw);
+        }
+        while let Ok(Some((unit, joy, sw))) = self.input_rx.try_recv() {
+            if self.netplay.is_some() {
+                // The netplay session dispatches the inputs once the peer's ones are known.
+                if unit == 0 {
+                    self.local_input = (joy, sw);
+                }
+                continue;
+            }
+            let lynx = match unit {
+                0 => Some(&mut self.lynx),
+                _ => self.guests.get_mut(unit - 1),
+            };
+            if let Some(lynx) = lynx {
+                lynx.set_joystick_u8(joy);
+                lynx.set_switches_u8(sw);
+            }
         }
         false
     }
@@ -96,38 +304,284 @@ impl PerFrameRunnerThread {
             self.config = config;
         }
 
+        if self.config.trace() && self.tracer.is_none() {
+            match TraceLogger::new(self.config.trace_settings()) {
+                Ok(tracer) => {
+                    trace!("Trace started.");
+                    self.tracer = Some(tracer);
+                }
+                Err(e) => {
+                    error!("Couldn't start trace. '{}'", e);
+                    self.stop_trace();
+                }
+            }
+        } else if !self.config.trace() && self.tracer.is_some() {
+            trace!("Trace stopped.");
+            self.tracer = None;
+        }
+
         if let Some(action) = self.config.take_action() {
             match action {
                 RunnerAction::LoadCart => {
                     self.load_cart();
-                    self.reset()
+                    self.reset();
+                    self.restart_guests();
+                }
+                RunnerAction::ReloadCart => {
+                    if self.load_cart() {
+                        self.osd_message("Cart reloaded".to_string());
+                    }
+                    self.reset();
+                    self.restart_guests();
                 }
                 RunnerAction::LoadROM => {
                     self.load_rom();
                     self.reset();
+                    self.restart_guests();
+                }
+                RunnerAction::Reset => {
+                    self.reset();
+                    self.restart_guests();
+                    self.osd_message("Reset".to_string());
                 }
-                RunnerAction::Reset => self.reset(),
                 RunnerAction::LoadState(file) => self.load_state(file),
                 RunnerAction::SaveState(file) => self.save_state(file),
+                RunnerAction::Snapshot => self.snapshot_requested = true,
+                RunnerAction::Profile(frames) => self.profiler = Some(Profiler::new(frames)),
+                RunnerAction::HostNetplay(address) => self.start_netplay(|hash| Netplay::host(address, hash)),
+                RunnerAction::JoinNetplay(address) => self.start_netplay(|hash| Netplay::join(address, hash)),
+                RunnerAction::StopNetplay => self.stop_netplay("Netplay stopped".to_string()),
+                RunnerAction::ImportEeprom(file) => self.import_eeprom(file),
+                RunnerAction::ExportEeprom(file) => self.export_eeprom(file),
+                RunnerAction::Shutdown => {
+                    self.flush_eeprom(true);
+                    self.shutdown = true;
+                }
+            }
+        }
+
+        if self.guests.len() != self.guest_count() {
+            self.sync_guests();
+        }
+
+        if *self.config.serial_backing() != self.serial_backing {
+            self.serial_backing = self.config.serial_backing().clone();
+            self.open_serial();
+        }
+
+        // Muted channels are left out by Mikey itself so the mix level doesn't change.
+        let enabled_channels = self.config.enabled_channels();
+        if self.enabled_channels != Some(enabled_channels) {
+            self.lynx.set_audio_channels_enabled(enabled_channels);
+            self.enabled_channels = Some(enabled_channels);
+        }
+    }
+
+    #[cfg(unix)]
+    fn open_serial(&mut self) {
+        self.serial = None;
+        let status = match SerialLink::open(&self.serial_backing) {
+            Err(e) => {
+                error!("Couldn't open the ComLynx serial link. '{}'", e);
+                format!("Serial link error: {}", e)
             }
+            Ok(None) => "Serial link closed".to_string(),
+            Ok(Some(link)) => {
+                let status = format!("ComLynx on {}", link.path().display());
+                self.serial = Some(link);
+                status
+            }
+        };
+        let _ = self.event_tx.try_send(Event::SerialStatus(status));
+    }
+
+    #[cfg(not(unix))]
+    fn open_serial(&mut self) {
+        if self.serial_backing != SerialBacking::Off {
+            error!("The ComLynx serial link is only available on unix systems.");
         }
     }
 
+    #[cfg(unix)]
+    fn serial(&mut self) {
+        let Some(serial) = self.serial.as_mut() else {
+            return;
+        };
+        if let Err(e) = serial.tick(&self.lynx) {
+            error!("ComLynx serial link error. '{}'", e);
+            self.serial = None;
+            let _ = self.event_tx.try_send(Event::SerialStatus(format!("Serial link error: {}", e)));
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn serial(&mut self) {}
+
+    fn netplay_status(&self, status: String) {
+        let _ = self.event_tx.try_send(Event::NetplayStatus(status));
+    }
+
+    fn start_netplay(&mut self, netplay: impl FnOnce(u32) -> io::Result<Netplay>) {
+        let Some(hash) = self.image_hash else {
+            error!("A cartridge has to be loaded before starting netplay.");
+            self.netplay_status("No cartridge".to_string());
+            return;
+        };
+        // Releases the port of a previous session first.
+        self.netplay = None;
+        match netplay(hash) {
+            Ok(netplay) => {
+                self.netplay = Some(netplay);
+                self.netplay_status("Waiting for peer".to_string());
+            }
+            Err(e) => {
+                error!("Couldn't start netplay. '{}'", e);
+                self.netplay_status(format!("Netplay failed: {}", e));
+            }
+        }
+    }
+
+    fn stop_netplay(&mut self, status: String) {
+        if self.netplay.take().is_some() {
+            self.local_input = (0, 0);
+            self.netplay_status(status);
+        }
+    }
+
+    /// Polls the netplay session, applies both units inputs and tells if the frame can be emulated.
+    fn netplay_ready(&mut self) -> bool {
+        let Some(netplay) = self.netplay.as_mut() else {
+            return true;
+        };
+        match netplay.poll() {
+            Err(e) => {
+                error!("Netplay error. '{}'", e);
+                self.stop_netplay(format!("Netplay error: {}", e));
+                return false;
+            }
+            Ok(true) => {
+                // Both peers start from a freshly reset pair of units.
+                let local_unit = netplay.local_unit();
+                self.reset();
+                self.restart_guests();
+                self.netplay_status(format!("Connected, playing unit {}", local_unit + 1));
+            }
+            Ok(false) => (),
+        }
+
+        if self.config.status() != RunnerStatus::Running || self.guests.is_empty() {
+            return false;
+        }
+
+        let netplay = self.netplay.as_mut().unwrap();
+        match netplay.frame_inputs(self.local_input) {
+            Err(e) => {
+                error!("Netplay error. '{}'", e);
+                self.stop_netplay(format!("Netplay error: {}", e));
+                false
+            }
+            Ok(None) => false,
+            Ok(Some([first, second])) => {
+                self.lynx.set_joystick_u8(first.0);
+                self.lynx.set_switches_u8(first.1);
+                self.guests[0].set_joystick_u8(second.0);
+                self.guests[0].set_switches_u8(second.1);
+                true
+            }
+        }
+    }
+
+    fn netplay_frame(&mut self) {
+        let Some(mut netplay) = self.netplay.take() else {
+            return;
+        };
+        match netplay.end_frame(|| self.state_hash()) {
+            Err(e) => {
+                error!("Netplay error. '{}'", e);
+                self.netplay = Some(netplay);
+                self.stop_netplay(format!("Netplay error: {}", e));
+            }
+            Ok(Some(frame)) => {
+                error!("Netplay desync detected at frame {}.", frame);
+                self.netplay_status(format!("Desync at frame {}", frame));
+                self.netplay = Some(netplay);
+            }
+            Ok(None) => self.netplay = Some(netplay),
+        }
+    }
+
+    /// Hash of the serialized state of every unit.
+    fn state_hash(&self) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        for lynx in std::iter::once(&self.lynx).chain(self.guests.iter()) {
+            let mut data: Vec<u8> = vec![0; lynx.serialize_size()];
+            if holani::serialize(lynx, data.as_mut_slice()).is_ok() {
+                hasher.update(&data);
+            }
+        }
+        hasher.finalize()
+    }
+
     fn reset(&mut self) {
         if self.config.cartridge().is_none() {
             return;
         }
+        self.flush_eeprom(true);
         self.lynx.reset();
+        self.enabled_channels = None;
+        self.restore_eeprom();
+    }
+
+    /// Loads the saved EEPROM of the running cart, if it has one.
+    fn restore_eeprom(&mut self) {
+        self.eeprom = self.cart_hash.and_then(|hash| EepromStore::open(&mut self.lynx, hash));
+    }
+
+    fn flush_eeprom(&mut self, force: bool) {
+        if let Some(eeprom) = self.eeprom.as_mut() {
+            eeprom.flush(&self.lynx, force);
+        }
+    }
+
+    fn import_eeprom(&mut self, file: PathBuf) {
+        let data = match std::fs::read(&file) {
+            Ok(data) => data,
+            Err(e) => {
+                error!("Couldn't read EEPROM '{}'. '{}'", file.display(), e);
+                return;
+            }
+        };
+        if !import_eeprom(&mut self.lynx, &data) {
+            self.osd_message("The cart has no EEPROM".to_string());
+            return;
+        }
+        self.flush_eeprom(true);
+        // The game reads its saves when it boots.
+        self.reset();
+        self.osd_message(format!("EEPROM imported from {}", file_label(&file)));
+    }
+
+    fn export_eeprom(&mut self, file: PathBuf) {
+        let Some(data) = self.lynx.eeprom_data() else {
+            self.osd_message("The cart has no EEPROM".to_string());
+            return;
+        };
+        match std::fs::write(&file, data) {
+            Ok(()) => self.osd_message(format!("EEPROM exported to {}", file_label(&file))),
+            Err(e) => error!("Couldn't write EEPROM '{}'. '{}'", file.display(), e),
+        }
     }
 
     fn load_state(&mut self, file: PathBuf) {
-        match std::fs::read(file) {
+        match std::fs::read(&file) {
             Err(e) => println!("deserialization error: {:?}", e),
             Ok(data) => match holani::deserialize(&data, &self.lynx) {
                 Err(e) => println!("deserialization error: {:?}", e),
                 Ok(mut lynx) => {
                     lynx.set_comlynx_cable(&self.lynx.comlynx_cable().clone());
                     self.lynx = lynx;
+                    self.enabled_channels = None;
+                    self.osd_message(format!("State loaded from {}", file_label(&file)));
                 }
             },
         };
@@ -136,36 +590,128 @@ impl PerFrameRunnerThread {
     fn save_state(&mut self, file: PathBuf) {
         let size = self.lynx.serialize_size();
         let mut data: Vec<u8> = vec![0; size];
-        match holani::serialize(&self.lynx, data.as_mut_slice()) {
-            Err(_) => panic!(),
-            Ok(_) => {
-                if std::fs::write(file, data).is_err() {
-                    panic!()
-                }
+        if let Err(e) = holani::serialize(&self.lynx, data.as_mut_slice()) {
+            error!("Couldn't serialize the state. '{:?}'", e);
+            self.osd_message("Couldn't save the state".to_string());
+            return;
+        }
+        match std::fs::write(&file, data) {
+            Ok(()) => self.osd_message(format!("State saved to {}", file_label(&file))),
+            Err(e) => {
+                error!("Couldn't save state to '{}'. '{}'", file.display(), e);
+                self.osd_message(format!("Couldn't save the state to {}", file_label(&file)));
             }
+        }
+    }
+
+    fn osd_message(&self, message: String) {
+        let _ = self.event_tx.try_send(Event::OsdMessage(message));
+    }
+
+    fn stats(&mut self, emulated: bool, sound_buffer: &HeapProd<i16>) {
+        let Some(fps) = self.frame_counter.frame(emulated) else {
+            return;
+        };
+        let audio_fill = match self.stream {
+            Some(_) => sound_buffer.occupied_len() as f32 / sound_buffer.capacity().get() as f32,
+            None => 0.,
         };
+        let _ = self.event_tx.try_send(Event::RunnerStats(RunnerStats {
+            fps,
+            refresh_rate: self.last_refresh_rate,
+            audio_fill,
+        }));
     }
 
-    fn load_cart(&mut self) {
-        if let Some(cart) = self.config.cartridge() {
-            let data = std::fs::read(cart);
-            if data.is_err() {
-                return;
+    fn cart_data(&self) -> Option<Vec<u8>> {
+        let file = self.config.cartridge().as_ref()?;
+        match read_cart_file(file, self.config.cartridge_entry()) {
+            Ok(data) => Some(data),
+            Err(e) => {
+                error!("Couldn't read cart '{}'. '{}'", file.display(), e);
+                None
             }
-            if self.lynx.load_cart_from_slice(&data.unwrap()).is_err() {
-                return;
+        }
+    }
+
+    /// Cart data ready to load, patched and with an LNX header for headerless dumps.
+    fn cart_image(&self) -> Option<CartImage> {
+        let data = self.cart_data()?;
+        let hash = cart_hash(&data);
+        let (data, patch_message) = self.patch_cart(data);
+        Some(CartImage {
+            data: self.add_header(data),
+            patch_message,
+            hash,
+        })
+    }
+
+    fn add_header(&self, data: Vec<u8>) -> Vec<u8> {
+        let Some(file) = self.config.cartridge() else {
+            return data;
+        };
+        let name = self.config.cartridge_entry().map(Path::new).unwrap_or(file);
+        if !is_headerless(name) {
+            return data;
+        }
+        let header = self.config.cart_header().cloned().unwrap_or_else(|| LnxHeader::detect(data.len()));
+        header.lnx_image(&data)
+    }
+
+    /// Applies the picked patch, or the one named like the cart file, to the cart data.
+    /// Returns the data to load and the message telling what was patched.
+    fn patch_cart(&self, data: Vec<u8>) -> (Vec<u8>, Option<String>) {
+        let patch = match self.config.patch() {
+            Some(patch) => Some(patch.clone()),
+            None if self.config.auto_patch() => self.config.cartridge().as_ref().and_then(|file| find_patch(file)),
+            None => None,
+        };
+        let Some(patch) = patch else {
+            return (data, None);
+        };
+        match apply_patch(&data, &patch) {
+            Ok(patched) => (patched, Some(format!("Patched with {}", file_label(&patch)))),
+            Err(e) => {
+                error!("Couldn't apply patch '{}'. '{}'", patch.display(), e);
+                (data, Some(format!("Patch {} not applied: {}", file_label(&patch), e)))
             }
-            trace!("Cart loaded.");
         }
     }
 
+    fn rom_data(&self) -> Option<Vec<u8>> {
+        std::fs::read(self.config.rom().as_ref()?).ok()
+    }
+
+    fn load_cart(&mut self) -> bool {
+        if self.config.cartridge().is_none() {
+            return false;
+        }
+        let Some(image) = self.cart_image() else {
+            return false;
+        };
+        self.flush_eeprom(true);
+        self.eeprom = None;
+        if self.lynx.load_cart_from_slice(&image.data).is_err() {
+            return false;
+        }
+        if let Some(message) = image.patch_message {
+            self.osd_message(message);
+        }
+        let hash = image.hash;
+        self.cart_hash = Some(hash);
+        self.image_hash = Some(crc32fast::hash(&image.data));
+        self.restore_eeprom();
+        let _ = self.event_tx.try_send(Event::CartLoaded(hash, self.config.cartridge().clone().unwrap()));
+        trace!("Cart loaded.");
+        true
+    }
+
     fn load_rom(&mut self) {
-        if let Some(rom) = self.config.rom() {
-            let data = std::fs::read(rom);
-            if data.is_err() {
+        if self.config.rom().is_some() {
+            let Some(data) = self.rom_data() else {
                 return;
-            }
-            if self.lynx.load_rom_from_slice(&data.unwrap()).is_err() {
+            };
+            if self.lynx.load_rom_from_slice(&data).is_err() {
                 return;
             }
             trace!("ROM loaded.");
@@ -177,6 +723,7 @@ impl RunnerThread for PerFrameRunnerThread {
     fn initialize(&mut self) -> Result<(), &str> {
         self.load_rom();
         self.load_cart();
+        self.sync_guests();
         self.rotation_tx.send(self.lynx.rotation()).unwrap();
         Ok(())
     }
@@ -238,6 +785,7 @@ impl RunnerThread for PerFrameRunnerThread {
 
         loop {
             if self.inputs() {
+                self.flush_eeprom(true);
                 return;
             }
 
@@ -253,18 +801,32 @@ impl RunnerThread for PerFrameRunnerThread {
                         .set_cartridge(PathBuf::from_str(shared_str).unwrap());
                     self.load_cart();
                     self.reset();
+                    self.restart_guests();
                 }
                 unsafe { *str_len = 0 };
             }
 
             self.config_update();
+            if self.shutdown {
+                trace!("Runner stopped.");
+                return;
+            }
 
             if self.config.cartridge().is_some() {
-                while self.config.status() == RunnerStatus::Running && !self.lynx.redraw_requested()
+                let ready = self.netplay_ready();
+                while ready && self.config.status() == RunnerStatus::Running && !self.lynx.redraw_requested()
                 {
                     self.lynx.tick();
+                    for guest in self.guests.iter_mut() {
+                        guest.tick();
+                    }
+                    self.trace();
+                    self.sprites();
+                    self.profile();
+                    self.serial();
                     self.sound(&mut sound_buffer);
                 }
+                self.cheats();
 
                 rf = self.lynx.display_refresh_rate();
                 if rf != self.last_refresh_rate {
@@ -274,6 +836,14 @@ impl RunnerThread for PerFrameRunnerThread {
                     trace!("set refresh rate to {} ({:?})", rf, self.frame_time);
                 }
                 self.display();
+                if ready && self.config.status() == RunnerStatus::Running {
+                    self.netplay_frame();
+                }
+                self.trace_frame();
+                self.snapshot();
+                self.profile_frame();
+                self.stats(ready && self.config.status() == RunnerStatus::Running, &sound_buffer);
+                self.flush_eeprom(false);
             }
 
             while self.next_lcd_refresh > Instant::now() {}
@@ -281,3 +851,10 @@ impl RunnerThread for PerFrameRunnerThread {
         }
     }
 }
+
+/// File name shown to the user for a state file.
+fn file_label(file: &Path) -> String {
+    file.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| file.display().to_string())
+}
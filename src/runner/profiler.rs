@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+use holani::lynx::Lynx;
+
+/// Crystal ticks per CPU cycle.
+pub(crate) const TICKS_PER_CYCLE: u64 = 4;
+
+pub(crate) struct ProfileReport {
+    /// Cycles spent per PC over the whole capture, most expensive first.
+    pub(crate) hotspots: Vec<(u16, u64)>,
+    /// Cycles spent per PC for every captured frame.
+    pub(crate) frames: Vec<HashMap<u16, u64>>,
+}
+
+pub(crate) struct Profiler {
+    frames_left: u32,
+    current: HashMap<u16, u64>,
+    frames: Vec<HashMap<u16, u64>>,
+}
+
+impl Profiler {
+    pub(crate) fn new(frames: u32) -> Self {
+        Self {
+            frames_left: frames.max(1),
+            current: HashMap::new(),
+            frames: vec![],
+        }
+    }
+
+    pub(crate) fn tick(&mut self, lynx: &Lynx) {
+        *self.current.entry(lynx.cpu_pc()).or_default() += 1;
+    }
+
+    /// Closes the current frame, returns true once the capture window is complete.
+    pub(crate) fn frame(&mut self) -> bool {
+        let frame = std::mem::take(&mut self.current)
+            .into_iter()
+            .map(|(pc, ticks)| (pc, ticks / TICKS_PER_CYCLE))
+            .collect();
+        self.frames.push(frame);
+        self.frames_left -= 1;
+        self.frames_left == 0
+    }
+
+    pub(crate) fn report(self) -> ProfileReport {
+        let mut totals: HashMap<u16, u64> = HashMap::new();
+        for frame in &self.frames {
+            for (pc, cycles) in frame {
+                *totals.entry(*pc).or_default() += cycles;
+            }
+        }
+        let mut hotspots: Vec<(u16, u64)> = totals.into_iter().collect();
+        hotspots.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        ProfileReport {
+            hotspots,
+            frames: self.frames,
+        }
+    }
+}
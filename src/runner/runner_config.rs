@@ -4,6 +4,32 @@ use serde::{Serialize, Deserialize};
 use gtk::gdk;
 use holani::cartridge::lnx_header::LNXRotation;
 use strum_macros::EnumIter;
+use crate::{cartridge::lnx_header::LnxHeader, cheats::Cheat};
+use super::netplay::DEFAULT_NETPLAY_PORT;
+
+pub(crate) const ALL_CHANNELS: u8 = 0x0f;
+pub(crate) const MAX_COMLYNX_UNITS: u8 = 8;
+pub(crate) const MAX_RECENT_CARTS: usize = 10;
+
+/// `folder` sub directory of the settings location, created if needed.
+pub(crate) fn data_dir(folder: &str) -> Option<PathBuf> {
+    let dir = confy::get_configuration_file_path("holani-gtk", None)
+        .ok()?
+        .parent()?
+        .join(folder);
+    std::fs::create_dir_all(&dir).ok()?;
+    Some(dir)
+}
+
+/// Path of `file_name` in the `folder` sub directory of the settings location, created if needed.
+pub(crate) fn data_file(folder: &str, file_name: &str) -> Option<PathBuf> {
+    Some(data_dir(folder)?.join(file_name))
+}
+
+/// Per cartridge file name, keyed by the cart content hash so renamed files are still found.
+pub(crate) fn cart_file_name(cart_hash: u32, extension: &str) -> String {
+    format!("{:08x}.{}", cart_hash, extension)
+}
 
 #[derive(Clone, Serialize, Deserialize, Debug, Default, Copy, EnumIter, PartialEq)]
 pub(crate) enum Input {
@@ -25,6 +51,147 @@ impl fmt::Display for Input {
     }
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, EnumIter)]
+pub(crate) enum Scaler {
+    #[default]
+    Nearest,
+    /// Nearest neighbour at the largest integer factor fitting the window.
+    Integer,
+    Scale2x,
+    Scale3x,
+    Xbr,
+    Bilinear,
+}
+
+impl Scaler {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Scaler::Nearest => "_Nearest",
+            Scaler::Integer => "_Integer only",
+            Scaler::Scale2x => "Scale_2x",
+            Scaler::Scale3x => "Scale_3x",
+            Scaler::Xbr => "_xBR-like",
+            Scaler::Bilinear => "_Bilinear",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, EnumIter)]
+pub(crate) enum Renderer {
+    /// GTK draws the frames, the scaler and filters are applied on the CPU.
+    #[default]
+    Software,
+    /// The frames go through a GLSL post-processing shader.
+    Gl,
+}
+
+impl Renderer {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Renderer::Software => "_Software",
+            Renderer::Gl => "_OpenGL",
+        }
+    }
+}
+
+/// What follows the reload when the watched cartridge file changes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, EnumIter)]
+pub(crate) enum WatchRestore {
+    #[default]
+    Reset,
+    /// Loads the state bookmarked with `Cartridge > Bookmark state`.
+    Bookmark,
+}
+
+impl WatchRestore {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            WatchRestore::Reset => "_Reset",
+            WatchRestore::Bookmark => "Restore _bookmark",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, EnumIter)]
+pub(crate) enum ColorProfile {
+    /// Colors as stored in the palette registers.
+    #[default]
+    Raw,
+    /// Darker and less saturated, like the backlit Lynx II LCD.
+    OriginalLcd,
+    Custom,
+}
+
+impl ColorProfile {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            ColorProfile::Raw => "Raw",
+            ColorProfile::OriginalLcd => "Original LCD",
+            ColorProfile::Custom => "Custom",
+        }
+    }
+
+    /// Adjustments applied to the frames, `custom` being the user tuned ones.
+    pub(crate) fn adjustments(&self, custom: ColorAdjustments) -> Option<ColorAdjustments> {
+        match self {
+            ColorProfile::Raw => None,
+            ColorProfile::OriginalLcd => Some(ColorAdjustments::ORIGINAL_LCD),
+            ColorProfile::Custom => Some(custom),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct ColorAdjustments {
+    pub(crate) gamma: f32,
+    /// Added to every channel, from -1 to 1.
+    pub(crate) brightness: f32,
+    pub(crate) contrast: f32,
+    /// 0 gives a grayscale image.
+    pub(crate) saturation: f32,
+}
+
+impl ColorAdjustments {
+    pub(crate) const ORIGINAL_LCD: ColorAdjustments = ColorAdjustments {
+        gamma: 1.3,
+        brightness: -0.02,
+        contrast: 0.92,
+        saturation: 0.8,
+    };
+}
+
+impl Default for ColorAdjustments {
+    fn default() -> Self {
+        Self {
+            gamma: 1.,
+            brightness: 0.,
+            contrast: 1.,
+            saturation: 1.,
+        }
+    }
+}
+
+/// Items of the on-screen display, each one can be shown or hidden.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, EnumIter)]
+pub(crate) enum OsdElement {
+    Fps,
+    Speed,
+    AudioBuffer,
+    Messages,
+}
+
+impl OsdElement {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            OsdElement::Fps => "_Frame rate",
+            OsdElement::Speed => "_Speed",
+            OsdElement::AudioBuffer => "_Audio buffer",
+            OsdElement::Messages => "_Messages",
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub(crate) enum RunnerStatus {
     Paused,
@@ -34,26 +201,141 @@ pub(crate) enum RunnerStatus {
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub(crate) enum RunnerAction {
     LoadCart,
+    ReloadCart,
     LoadROM,
     Reset,
     LoadState(PathBuf),
     SaveState(PathBuf),
+    Snapshot,
+    Profile(u32),
+    HostNetplay(String),
+    JoinNetplay(String),
+    StopNetplay,
+    ImportEeprom(PathBuf),
+    ExportEeprom(PathBuf),
+    /// Stops the runner thread.
+    Shutdown,
+}
+
+/// What the ComLynx line is attached to, besides the emulated units.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) enum SerialBacking {
+    #[default]
+    Off,
+    /// A new pseudo-terminal, its path is reported once opened.
+    Pty,
+    Device(PathBuf),
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub(crate) struct TraceSettings {
+    file: PathBuf,
+    range_start: u16,
+    range_end: u16,
+    frame_limit: u32,
+}
+
+impl Default for TraceSettings {
+    fn default() -> Self {
+        Self {
+            file: PathBuf::from("holani-trace.log"),
+            range_start: 0x0000,
+            range_end: 0xffff,
+            frame_limit: 0,
+        }
+    }
+}
+
+impl TraceSettings {
+    pub(crate) fn file(&self) -> &PathBuf {
+        &self.file
+    }
+
+    pub(crate) fn set_file(&mut self, file: PathBuf) {
+        self.file = file;
+    }
+
+    pub(crate) fn range_start(&self) -> u16 {
+        self.range_start
+    }
+
+    pub(crate) fn range_end(&self) -> u16 {
+        self.range_end
+    }
+
+    pub(crate) fn set_range(&mut self, start: u16, end: u16) {
+        self.range_start = start.min(end);
+        self.range_end = start.max(end);
+    }
+
+    /// Number of frames to trace, 0 means no limit.
+    pub(crate) fn frame_limit(&self) -> u32 {
+        self.frame_limit
+    }
+
+    pub(crate) fn set_frame_limit(&mut self, frame_limit: u32) {
+        self.frame_limit = frame_limit;
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct RunnerConfig {
     #[serde(skip)]
     cartridge: Option<PathBuf>,
+    #[serde(skip)]
+    cartridge_entry: Option<String>,
+    #[serde(skip)]
+    patch: Option<PathBuf>,
+    #[serde(skip)]
+    cart_header: Option<LnxHeader>,
+    auto_patch: bool,
+    watch_cart: bool,
+    watch_restore: WatchRestore,
     rom: Option<PathBuf>,
     button_mapping: HashMap<String, Input>,
+    unit_button_mappings: Vec<HashMap<String, Input>>,
+    library_folders: Vec<PathBuf>,
+    recent_carts: Vec<PathBuf>,
+    auto_resume: bool,
     mute: bool,
     comlynx: bool,
+    comlynx_units: u8,
+    netplay_listen_address: String,
+    netplay_address: String,
+    #[serde(skip)]
+    netplay: bool,
+    serial_backing: SerialBacking,
+    ghosting: u8,
+    scaler: Scaler,
+    lcd_grid: bool,
+    renderer: Renderer,
+    shader: Option<String>,
+    osd: Vec<OsdElement>,
+    color_profile: ColorProfile,
+    color_adjustments: ColorAdjustments,
+    window_width: i32,
+    window_height: i32,
+    window_maximized: bool,
     status: RunnerStatus,
     rotation: LNXRotation,
     #[serde(skip)]
     action: Option<RunnerAction>,
     #[serde(skip)]
     single_instance: bool,
+    #[serde(skip)]
+    fullscreen: bool,
+    trace_settings: TraceSettings,
+    #[serde(skip)]
+    trace: bool,
+    #[serde(skip)]
+    debug_snapshots: bool,
+    #[serde(skip)]
+    muted_channels: u8,
+    #[serde(skip)]
+    solo_channels: u8,
+    #[serde(skip)]
+    cheats: Vec<Cheat>,
 }
 
 impl Default for RunnerConfig {
@@ -61,13 +343,46 @@ impl Default for RunnerConfig {
         let mut slf = Self {
             rom: None,
             cartridge: None,
+            cartridge_entry: None,
+            patch: None,
+            cart_header: None,
+            auto_patch: true,
+            watch_cart: false,
+            watch_restore: WatchRestore::Reset,
+            library_folders: vec![],
+            recent_carts: vec![],
+            auto_resume: false,
             mute: false,
             comlynx: false,
+            comlynx_units: 1,
+            netplay_listen_address: format!("0.0.0.0:{}", DEFAULT_NETPLAY_PORT),
+            netplay_address: format!("127.0.0.1:{}", DEFAULT_NETPLAY_PORT),
+            netplay: false,
+            serial_backing: SerialBacking::Off,
+            ghosting: 0,
+            scaler: Scaler::Nearest,
+            lcd_grid: false,
+            renderer: Renderer::Software,
+            shader: None,
+            osd: vec![OsdElement::Messages],
+            color_profile: ColorProfile::Raw,
+            color_adjustments: ColorAdjustments::default(),
+            window_width: 0,
+            window_height: 0,
+            window_maximized: false,
             button_mapping: HashMap::new(),
+            unit_button_mappings: vec![],
             status: RunnerStatus::Running,
             rotation: LNXRotation::None,
             action: None,
             single_instance: false,
+            fullscreen: false,
+            trace_settings: TraceSettings::default(),
+            trace: false,
+            debug_snapshots: false,
+            muted_channels: 0,
+            solo_channels: 0,
+            cheats: vec![],
         };
 
         slf.set_button_mapping(gdk::Key::Up, Input::Up);
@@ -80,10 +395,28 @@ impl Default for RunnerConfig {
         slf.set_button_mapping(gdk::Key::_2, Input::Option2);
         slf.set_button_mapping(gdk::Key::p, Input::Pause);
 
+        slf.set_unit_button_mapping(1, gdk::Key::KP_8, Input::Up);
+        slf.set_unit_button_mapping(1, gdk::Key::KP_2, Input::Down);
+        slf.set_unit_button_mapping(1, gdk::Key::KP_4, Input::Left);
+        slf.set_unit_button_mapping(1, gdk::Key::KP_6, Input::Right);
+        slf.set_unit_button_mapping(1, gdk::Key::KP_0, Input::Outside);
+        slf.set_unit_button_mapping(1, gdk::Key::KP_Decimal, Input::Inside);
+        slf.set_unit_button_mapping(1, gdk::Key::KP_7, Input::Option1);
+        slf.set_unit_button_mapping(1, gdk::Key::KP_9, Input::Option2);
+        slf.set_unit_button_mapping(1, gdk::Key::KP_5, Input::Pause);
+
         slf
     }
 }
 
+fn map_button(mapping: &mut HashMap<String, Input>, key: String, btn: Input) {
+    let k = key.to_lowercase();
+    if let Some((to_remove, _)) = mapping.clone().iter().find(|(_, v)| **v == btn) {
+        mapping.remove(to_remove);
+    }
+    mapping.insert(k, btn);
+}
+
 impl RunnerConfig {
     pub(crate) fn rom(&self) -> &Option<PathBuf> {
         &self.rom
@@ -99,10 +432,45 @@ impl RunnerConfig {
 
     pub(crate) fn set_cartridge(&mut self, cartridge: PathBuf) {
         self.cartridge = Some(cartridge);
+        self.cartridge_entry = None;
+        self.patch = None;
+        self.cart_header = None;
+    }
+
+    /// File loaded from the cartridge archive, the first cartridge in it when `None`.
+    pub(crate) fn cartridge_entry(&self) -> Option<&str> {
+        self.cartridge_entry.as_deref()
+    }
+
+    pub(crate) fn set_cartridge_entry(&mut self, cartridge_entry: Option<String>) {
+        self.cartridge_entry = cartridge_entry;
+    }
+
+    /// Header put in front of a headerless cartridge, detected from the file size when `None`.
+    pub(crate) fn cart_header(&self) -> Option<&LnxHeader> {
+        self.cart_header.as_ref()
+    }
+
+    pub(crate) fn set_cart_header(&mut self, cart_header: Option<LnxHeader>) {
+        self.cart_header = cart_header;
+    }
+
+    /// IPS, BPS or UPS patch picked by the user for the cartridge.
+    pub(crate) fn patch(&self) -> &Option<PathBuf> {
+        &self.patch
+    }
+
+    pub(crate) fn set_patch(&mut self, patch: Option<PathBuf>) {
+        self.patch = patch;
+    }
+
+    /// Applies the patch named like the cartridge file when none was picked.
+    pub(crate) fn auto_patch(&self) -> bool {
+        self.auto_patch
     }
 
-    pub(crate) fn button_mapping(&self) -> &HashMap<String, Input> {
-        &self.button_mapping
+    pub(crate) fn set_auto_patch(&mut self, auto_patch: bool) {
+        self.auto_patch = auto_patch;
     }
 
     pub(crate) fn set_button_mapping(&mut self, key: gdk::Key, btn: Input) {
@@ -110,13 +478,83 @@ impl RunnerConfig {
     }
 
     pub(crate) fn set_button_mapping_as_str(&mut self, key: String, btn: Input) {
-        let k = key.to_lowercase();
-        if let Some((to_remove, _)) = self.button_mapping.clone().iter().find(|(_, v)| **v == btn) {
-            self.button_mapping.remove(to_remove);
+        map_button(&mut self.button_mapping, key, btn);
+    }
+
+    /// Buttons mapping of a ComLynx unit, unit 0 being the main Lynx.
+    pub(crate) fn unit_button_mapping(&self, unit: usize) -> Option<&HashMap<String, Input>> {
+        match unit {
+            0 => Some(&self.button_mapping),
+            _ => self.unit_button_mappings.get(unit - 1),
+        }
+    }
+
+    pub(crate) fn set_unit_button_mapping(&mut self, unit: usize, key: gdk::Key, btn: Input) {
+        self.set_unit_button_mapping_as_str(unit, key.name().unwrap().to_string(), btn);
+    }
+
+    pub(crate) fn set_unit_button_mapping_as_str(&mut self, unit: usize, key: String, btn: Input) {
+        match unit {
+            0 => self.set_button_mapping_as_str(key, btn),
+            _ => {
+                if self.unit_button_mappings.len() < unit {
+                    self.unit_button_mappings.resize(unit, HashMap::new());
+                }
+                map_button(&mut self.unit_button_mappings[unit - 1], key, btn);
+            }
         }
-        self.button_mapping.insert(k, btn);
     }
     
+    /// Cartridges loaded lately, the most recent first.
+    pub(crate) fn recent_carts(&self) -> &Vec<PathBuf> {
+        &self.recent_carts
+    }
+
+    pub(crate) fn add_recent_cart(&mut self, cartridge: PathBuf) {
+        self.recent_carts.retain(|cart| *cart != cartridge);
+        self.recent_carts.insert(0, cartridge);
+        self.recent_carts.truncate(MAX_RECENT_CARTS);
+    }
+
+    pub(crate) fn clear_recent_carts(&mut self) {
+        self.recent_carts.clear();
+    }
+
+    /// Reloads the cartridge when its file changes on disk.
+    pub(crate) fn watch_cart(&self) -> bool {
+        self.watch_cart
+    }
+
+    pub(crate) fn set_watch_cart(&mut self, watch_cart: bool) {
+        self.watch_cart = watch_cart;
+    }
+
+    pub(crate) fn watch_restore(&self) -> WatchRestore {
+        self.watch_restore
+    }
+
+    pub(crate) fn set_watch_restore(&mut self, watch_restore: WatchRestore) {
+        self.watch_restore = watch_restore;
+    }
+
+    /// Saves a state on quit and restores it when the same cart is loaded again.
+    pub(crate) fn auto_resume(&self) -> bool {
+        self.auto_resume
+    }
+
+    pub(crate) fn set_auto_resume(&mut self, auto_resume: bool) {
+        self.auto_resume = auto_resume;
+    }
+
+    /// Folders scanned for cartridges by the library.
+    pub(crate) fn library_folders(&self) -> &Vec<PathBuf> {
+        &self.library_folders
+    }
+
+    pub(crate) fn set_library_folders(&mut self, library_folders: Vec<PathBuf>) {
+        self.library_folders = library_folders;
+    }
+
     pub(crate) fn mute(&self) -> bool {
         self.mute
     }
@@ -132,6 +570,146 @@ impl RunnerConfig {
     pub(crate) fn set_comlynx(&mut self, comlynx: bool) {
         self.comlynx = comlynx;
     }
+
+    pub(crate) fn comlynx_units(&self) -> u8 {
+        self.comlynx_units.clamp(1, MAX_COMLYNX_UNITS)
+    }
+
+    pub(crate) fn set_comlynx_units(&mut self, comlynx_units: u8) {
+        self.comlynx_units = comlynx_units.clamp(1, MAX_COMLYNX_UNITS);
+    }
+
+    /// Number of Lynx units emulated, the main one included. A netplay session always runs two.
+    pub(crate) fn units(&self) -> usize {
+        match (self.netplay, self.comlynx) {
+            (true, _) => 2,
+            (false, true) => self.comlynx_units() as usize,
+            (false, false) => 1,
+        }
+    }
+
+    /// A netplay session was started, not saved so the next run keeps the ComLynx setup.
+    pub(crate) fn set_netplay(&mut self, netplay: bool) {
+        self.netplay = netplay;
+    }
+
+    /// Last `host:port` a netplay session was hosted on.
+    pub(crate) fn netplay_listen_address(&self) -> &str {
+        &self.netplay_listen_address
+    }
+
+    pub(crate) fn set_netplay_listen_address(&mut self, netplay_listen_address: String) {
+        self.netplay_listen_address = netplay_listen_address;
+    }
+
+    /// Last `host:port` joined.
+    pub(crate) fn netplay_address(&self) -> &str {
+        &self.netplay_address
+    }
+
+    pub(crate) fn set_netplay_address(&mut self, netplay_address: String) {
+        self.netplay_address = netplay_address;
+    }
+
+    pub(crate) fn serial_backing(&self) -> &SerialBacking {
+        &self.serial_backing
+    }
+
+    pub(crate) fn set_serial_backing(&mut self, serial_backing: SerialBacking) {
+        self.serial_backing = serial_backing;
+    }
+
+    /// LCD persistence in percent, 0 when disabled.
+    pub(crate) fn ghosting(&self) -> u8 {
+        self.ghosting
+    }
+
+    pub(crate) fn set_ghosting(&mut self, ghosting: u8) {
+        self.ghosting = ghosting;
+    }
+
+    pub(crate) fn scaler(&self) -> Scaler {
+        self.scaler
+    }
+
+    pub(crate) fn set_scaler(&mut self, scaler: Scaler) {
+        self.scaler = scaler;
+    }
+
+    pub(crate) fn lcd_grid(&self) -> bool {
+        self.lcd_grid
+    }
+
+    pub(crate) fn set_lcd_grid(&mut self, lcd_grid: bool) {
+        self.lcd_grid = lcd_grid;
+    }
+
+    pub(crate) fn renderer(&self) -> Renderer {
+        self.renderer
+    }
+
+    pub(crate) fn set_renderer(&mut self, renderer: Renderer) {
+        self.renderer = renderer;
+    }
+
+    /// File name of the post-processing shader in the shaders directory, used by the GL renderer.
+    pub(crate) fn shader(&self) -> Option<&str> {
+        self.shader.as_deref()
+    }
+
+    pub(crate) fn set_shader(&mut self, shader: Option<String>) {
+        self.shader = shader;
+    }
+
+    pub(crate) fn color_profile(&self) -> ColorProfile {
+        self.color_profile
+    }
+
+    pub(crate) fn set_color_profile(&mut self, color_profile: ColorProfile) {
+        self.color_profile = color_profile;
+    }
+
+    /// Adjustments of the custom color profile.
+    pub(crate) fn color_adjustments(&self) -> ColorAdjustments {
+        self.color_adjustments
+    }
+
+    pub(crate) fn set_color_adjustments(&mut self, color_adjustments: ColorAdjustments) {
+        self.color_adjustments = color_adjustments;
+    }
+
+    /// Whether an item of the on-screen display is shown.
+    pub(crate) fn osd(&self, element: OsdElement) -> bool {
+        self.osd.contains(&element)
+    }
+
+    pub(crate) fn set_osd(&mut self, element: OsdElement, shown: bool) {
+        self.osd.retain(|e| *e != element);
+        if shown {
+            self.osd.push(element);
+        }
+    }
+
+    /// Main window size of the previous run, if any.
+    pub(crate) fn window_size(&self) -> Option<(i32, i32)> {
+        match (self.window_width, self.window_height) {
+            (w, h) if w > 0 && h > 0 => Some((w, h)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn set_window_size(&mut self, width: i32, height: i32) {
+        self.window_width = width;
+        self.window_height = height;
+    }
+
+    pub(crate) fn window_maximized(&self) -> bool {
+        self.window_maximized
+    }
+
+    pub(crate) fn set_window_maximized(&mut self, window_maximized: bool) {
+        self.window_maximized = window_maximized;
+    }
     
     pub(crate) fn status(&self) -> RunnerStatus {
         self.status
@@ -164,4 +742,67 @@ impl RunnerConfig {
     pub(crate) fn set_single_instance(&mut self, single_instance: bool) {
         self.single_instance = single_instance;
     }
+
+    /// Start in fullscreen.
+    pub(crate) fn fullscreen(&self) -> bool {
+        self.fullscreen
+    }
+
+    pub(crate) fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.fullscreen = fullscreen;
+    }
+
+    pub(crate) fn trace_settings(&self) -> &TraceSettings {
+        &self.trace_settings
+    }
+
+    pub(crate) fn set_trace_settings(&mut self, trace_settings: TraceSettings) {
+        self.trace_settings = trace_settings;
+    }
+
+    pub(crate) fn trace(&self) -> bool {
+        self.trace
+    }
+
+    pub(crate) fn set_trace(&mut self, trace: bool) {
+        self.trace = trace;
+    }
+
+    /// Sends a debug snapshot every frame.
+    pub(crate) fn debug_snapshots(&self) -> bool {
+        self.debug_snapshots
+    }
+
+    pub(crate) fn set_debug_snapshots(&mut self, debug_snapshots: bool) {
+        self.debug_snapshots = debug_snapshots;
+    }
+
+    pub(crate) fn muted_channels(&self) -> u8 {
+        self.muted_channels
+    }
+
+    pub(crate) fn solo_channels(&self) -> u8 {
+        self.solo_channels
+    }
+
+    pub(crate) fn set_channels(&mut self, muted: u8, solo: u8) {
+        self.muted_channels = muted;
+        self.solo_channels = solo;
+    }
+
+    /// Audio channels mask once mute and solo are applied, soloed channels take precedence.
+    pub(crate) fn enabled_channels(&self) -> u8 {
+        match self.solo_channels {
+            0 => !self.muted_channels & ALL_CHANNELS,
+            solo => solo,
+        }
+    }
+
+    pub(crate) fn cheats(&self) -> &Vec<Cheat> {
+        &self.cheats
+    }
+
+    pub(crate) fn set_cheats(&mut self, cheats: Vec<Cheat>) {
+        self.cheats = cheats;
+    }
 }
@@ -0,0 +1,185 @@
+use std::{
+    ffi::CStr,
+    fs::File,
+    io::{self, Read, Write},
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    path::{Path, PathBuf},
+};
+use holani::lynx::Lynx;
+use super::{runner_config::SerialBacking, CRYSTAL_FREQUENCY};
+
+const TIM4BKUP: u16 = 0xfd10;
+const TIM4CTLA: u16 = 0xfd11;
+/// Start, 8 data, parity and stop bits.
+const BITS_PER_BYTE: u32 = 11;
+
+const BAUD_RATES: [(u32, libc::speed_t); 10] = [
+    (1200, libc::B1200), (2400, libc::B2400), (4800, libc::B4800), (9600, libc::B9600),
+    (19200, libc::B19200), (38400, libc::B38400), (57600, libc::B57600), (115200, libc::B115200),
+    (230400, libc::B230400), (460800, libc::B460800),
+];
+
+/// ComLynx baud rate programmed by the cart, Timer 4 clocks the UART at 8 ticks per bit.
+pub(crate) fn comlynx_baud_rate(lynx: &Lynx) -> u32 {
+    let backup = lynx.cpu_mem(TIM4BKUP) as u32;
+    let clock_select = (lynx.cpu_mem(TIM4CTLA) & 0x07).min(5) as u32;
+    1_000_000 / (1 << clock_select) / (backup + 1) / 8
+}
+
+fn check(ret: libc::c_int) -> io::Result<libc::c_int> {
+    match ret {
+        -1 => Err(io::Error::last_os_error()),
+        r => Ok(r),
+    }
+}
+
+fn set_raw(fd: libc::c_int, baud: Option<u32>) -> io::Result<()> {
+    unsafe {
+        let mut termios: libc::termios = std::mem::zeroed();
+        check(libc::tcgetattr(fd, &mut termios))?;
+        libc::cfmakeraw(&mut termios);
+        if let Some(baud) = baud {
+            let (_, speed) = BAUD_RATES
+                .iter()
+                .min_by_key(|(rate, _)| rate.abs_diff(baud))
+                .unwrap();
+            check(libc::cfsetspeed(&mut termios, *speed))?;
+        }
+        check(libc::tcsetattr(fd, libc::TCSANOW, &termios))?;
+        let flags = check(libc::fcntl(fd, libc::F_GETFL))?;
+        check(libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK))?;
+    }
+    Ok(())
+}
+
+/// Bridges the emulated ComLynx line to a pty or a serial device. Bytes coming from the host are
+/// put on the cable at the pace of the baud rate programmed by the cart.
+pub(crate) struct SerialLink {
+    file: File,
+    path: PathBuf,
+    is_device: bool,
+    // Keeps the pty open when no host tool is attached.
+    _slave: Option<OwnedFd>,
+    baud: u32,
+    ticks_per_bit: u64,
+    next_sample_tick: u64,
+    next_rx_tick: u64,
+    tick: u64,
+    rx: Vec<u8>,
+    /// Byte put on the cable for the cart.
+    sent: Option<u16>,
+    /// Byte transmitted by the cart and already forwarded.
+    seen: Option<u16>,
+}
+
+impl SerialLink {
+    pub(crate) fn open(backing: &SerialBacking) -> io::Result<Option<Self>> {
+        match backing {
+            SerialBacking::Off => Ok(None),
+            SerialBacking::Pty => Self::open_pty().map(Some),
+            SerialBacking::Device(path) => Self::open_device(path).map(Some),
+        }
+    }
+
+    fn new(file: File, path: PathBuf, is_device: bool, slave: Option<OwnedFd>) -> Self {
+        Self {
+            file,
+            path,
+            is_device,
+            _slave: slave,
+            baud: 0,
+            ticks_per_bit: 0,
+            next_sample_tick: 0,
+            next_rx_tick: 0,
+            tick: 0,
+            rx: vec![],
+            sent: None,
+            seen: None,
+        }
+    }
+
+    fn open_pty() -> io::Result<Self> {
+        let (mut master, mut slave) = (0, 0);
+        unsafe {
+            check(libc::openpty(&mut master, &mut slave, std::ptr::null_mut(), std::ptr::null(), std::ptr::null()))?;
+        }
+        let (master, slave) = unsafe { (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave)) };
+        let name = unsafe { libc::ttyname(slave.as_raw_fd()) };
+        if name.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let path = PathBuf::from(unsafe { CStr::from_ptr(name) }.to_string_lossy().to_string());
+        set_raw(slave.as_raw_fd(), None)?;
+        set_raw(master.as_raw_fd(), None)?;
+        Ok(Self::new(File::from(master), path, false, Some(slave)))
+    }
+
+    fn open_device(path: &Path) -> io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)?;
+        set_raw(file.as_raw_fd(), None)?;
+        Ok(Self::new(file, path.to_path_buf(), true, None))
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Called on every Lynx tick, the line is sampled once per bit.
+    pub(crate) fn tick(&mut self, lynx: &Lynx) -> io::Result<()> {
+        self.tick += 1;
+        if self.tick < self.next_sample_tick {
+            return Ok(());
+        }
+        self.update_baud(lynx)?;
+        self.next_sample_tick = self.tick + self.ticks_per_bit;
+
+        let cable = lynx.comlynx_cable();
+        if !cable.is_set() {
+            self.sent = None;
+            self.seen = None;
+        } else if Some(cable.data()) != self.sent && Some(cable.data()) != self.seen {
+            self.seen = Some(cable.data());
+            match self.file.write_all(&[cable.data() as u8]) {
+                // Nobody reads the other end, the byte is lost like on an unplugged cable.
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => (),
+                r => r?,
+            }
+        }
+
+        if self.tick < self.next_rx_tick {
+            return Ok(());
+        }
+        self.next_rx_tick = self.tick + self.ticks_per_bit * BITS_PER_BYTE as u64;
+
+        let mut data = [0; 64];
+        match self.file.read(&mut data) {
+            Ok(n) => self.rx.extend_from_slice(&data[..n]),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => (),
+            // No host tool attached to the pty.
+            Err(e) if e.raw_os_error() == Some(libc::EIO) => (),
+            Err(e) => return Err(e),
+        }
+        if self.sent.is_none() && !self.rx.is_empty() {
+            let byte = self.rx.remove(0) as u16;
+            cable.set(byte);
+            self.sent = Some(byte);
+        }
+        Ok(())
+    }
+
+    fn update_baud(&mut self, lynx: &Lynx) -> io::Result<()> {
+        let baud = comlynx_baud_rate(lynx).max(1);
+        if baud == self.baud {
+            return Ok(());
+        }
+        self.baud = baud;
+        self.ticks_per_bit = (CRYSTAL_FREQUENCY / baud) as u64;
+        if self.is_device {
+            set_raw(self.file.as_raw_fd(), Some(baud))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+const STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Emulation speed figures, sent to the UI once per second.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct RunnerStats {
+    /// Frames emulated per second.
+    pub(crate) fps: f64,
+    /// LCD refresh rate programmed by the cart.
+    pub(crate) refresh_rate: f64,
+    /// Share of the audio ring buffer waiting to be played, from 0 to 1.
+    pub(crate) audio_fill: f32,
+}
+
+impl RunnerStats {
+    /// Emulation speed in percent of the real hardware.
+    pub(crate) fn speed(&self) -> f64 {
+        match self.refresh_rate > 0. {
+            true => self.fps * 100. / self.refresh_rate,
+            false => 0.,
+        }
+    }
+}
+
+pub(crate) struct FrameCounter {
+    frames: u32,
+    start: Instant,
+}
+
+impl FrameCounter {
+    pub(crate) fn new() -> Self {
+        Self {
+            frames: 0,
+            start: Instant::now(),
+        }
+    }
+
+    /// Counts a frame, returns the measured frame rate once per interval.
+    pub(crate) fn frame(&mut self, emulated: bool) -> Option<f64> {
+        if emulated {
+            self.frames += 1;
+        }
+        let elapsed = self.start.elapsed();
+        if elapsed < STATS_INTERVAL {
+            return None;
+        }
+        let fps = self.frames as f64 / elapsed.as_secs_f64();
+        self.frames = 0;
+        self.start = Instant::now();
+        Some(fps)
+    }
+}
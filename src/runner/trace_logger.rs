@@ -0,0 +1,72 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+use holani::lynx::Lynx;
+use super::{disassembler::disassemble, runner_config::TraceSettings};
+
+pub(crate) struct TraceLogger {
+    writer: BufWriter<File>,
+    range_start: u16,
+    range_end: u16,
+    frames_left: Option<u32>,
+}
+
+impl TraceLogger {
+    pub(crate) fn new(settings: &TraceSettings) -> std::io::Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(settings.file())?),
+            range_start: settings.range_start(),
+            range_end: settings.range_end(),
+            frames_left: match settings.frame_limit() {
+                0 => None,
+                limit => Some(limit),
+            },
+        })
+    }
+
+    /// Called after every crystal tick, writes a line each time the CPU fetches an opcode, so
+    /// branches to themselves are logged on every pass.
+    pub(crate) fn tick(&mut self, lynx: &Lynx) -> std::io::Result<()> {
+        if !lynx.cpu_instruction_start() {
+            return Ok(());
+        }
+
+        let pc = lynx.cpu_pc();
+        if pc < self.range_start || pc > self.range_end {
+            return Ok(());
+        }
+
+        let instruction = disassemble(pc, |addr| lynx.cpu_mem(addr));
+        let opcodes = instruction
+            .bytes
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<String>>()
+            .join(" ");
+        let (a, x, y, s, p) = lynx.cpu_registers();
+
+        writeln!(
+            self.writer,
+            "{:>12} {:04X}  {:<8}  {:<14} A:{:02X} X:{:02X} Y:{:02X} S:{:02X} P:{:02X}",
+            lynx.cpu_cycles(), pc, opcodes, instruction.text, a, x, y, s, p
+        )
+    }
+
+    /// Called once per displayed frame, returns true when the frame limit has been reached.
+    pub(crate) fn frame(&mut self) -> bool {
+        match self.frames_left.as_mut() {
+            None => false,
+            Some(left) => {
+                *left = left.saturating_sub(1);
+                *left == 0
+            }
+        }
+    }
+}
+
+impl Drop for TraceLogger {
+    fn drop(&mut self) {
+        let _ = self.writer.flush();
+    }
+}